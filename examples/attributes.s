@@ -0,0 +1,15 @@
+# llvm-mc --triple=riscv32 -mattr=+m,+a,+f,+d,+c -target-abi ilp32d -filetype=obj attributes.s -o attributes.o
+# rust-lld -flavor gnu attributes.o -o attributes.elf
+.attribute arch, "rv32imafdc"
+.attribute stack_align, 16
+.attribute unaligned_access, 1
+.attribute priv_spec, 1
+.attribute priv_spec_minor, 12
+
+.section .text
+.global _start
+_start:
+    fld fa0, 0(sp)
+    fadd.d fa0, fa0, fa0
+    li a7, 93
+    ecall
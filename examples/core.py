@@ -0,0 +1,43 @@
+# python3 core.py
+# Writes core.elf, a core dump in the layout the RV32 Linux kernel uses: two threads that each have NT_PRSTATUS and
+# NT_FPREGSET notes, NT_PRPSINFO and NT_FILE, a text segment that was not dumped and two adjacent dumped segments.
+import struct
+
+def note(ty, desc, name=b"CORE\0"):
+    pad = lambda data: data + b"\0" * (-len(data) % 4)
+    return struct.pack("<III", len(name), len(desc), ty) + pad(name) + pad(desc)
+
+def prstatus(pid, signal, pc):
+    registers = [pc] + [index * 0x100 for index in range(1, 32)]
+    return struct.pack("<iiihxxII4i8I32Ii", signal, 0, 0, signal, 0, 0, pid, 1, pid, pid, *[0] * 8, *registers, 1)
+
+def fpregset(base):
+    return struct.pack("<32QI4x", *[struct.unpack("<Q", struct.pack("<d", base + index))[0] for index in range(32)], 0x60)
+
+prpsinfo = struct.pack("<BBBbIII4i16s80s", 0, ord("R"), 0, 0, 0x400600, 1000, 1000, 42, 1, 42, 42, b"crash", b"./crash --now")
+files = struct.pack("<II", 1, 0x1000) + struct.pack("<III", 0x10000, 0x11000, 0) + b"/bin/crash\0"
+notes = b"".join([
+    note(1, prstatus(42, 11, 0x10074)),
+    note(3, prpsinfo),
+    note(0x46494C45, files),
+    note(2, fpregset(1.0)),
+    note(1, prstatus(43, 0, 0x10080)),
+    note(2, fpregset(2.0)),
+])
+
+# (address, memory size, file size, flags)
+segments = [(0x10000, 0x1000, 0, 5), (0x11000, 0x1000, 0x1000, 6), (0x12000, 0x1000, 0x1000, 6)]
+ph_offset = 52
+data_offset = ph_offset + 32 * (len(segments) + 1)
+programs = [struct.pack("<8I", 4, data_offset, 0, 0, len(notes), 0, 0, 4)]
+contents = notes
+for address, mem_size, file_size, flags in segments:
+    contents += b"\0" * (-(data_offset + len(contents)) % 0x1000)
+    offset = data_offset + len(contents)
+    programs.append(struct.pack("<8I", 1, offset, address, 0, file_size, mem_size, flags, 0x1000))
+    # Fill each page with the page number so reads show which segment they came from
+    contents += bytes([address >> 12 & 0xFF]) * file_size
+
+ident = b"\x7fELF\x01\x01\x01" + b"\0" * 9
+header = ident + struct.pack("<HHIIIIIHHHHHH", 4, 243, 1, 0, ph_offset, 0, 0, 52, 32, len(programs), 40, 0, 0)
+open("core.elf", "wb").write(header + b"".join(programs) + contents)
@@ -0,0 +1,40 @@
+# llvm-mc --triple=riscv32 -mattr=+c -filetype=obj dynamic.s -o dynamic.o
+# rust-lld -flavor gnu -shared -soname libdynamic.so --hash-style=both -z now dynamic.o -o libdynamic.so
+.section .text
+.global add
+.type add, @function
+add:
+    add a0, a0, a1
+    ret
+.size add, .-add
+
+.global greet
+.type greet, @function
+greet:
+    addi sp, sp, -16
+    sw ra, 12(sp)
+    lla a0, message
+    call puts@plt
+    lw ra, 12(sp)
+    addi sp, sp, 16
+    ret
+.size greet, .-greet
+
+init:
+    ret
+
+.section .init_array, "aw"
+.p2align 2
+    .word init
+
+.section .rodata
+message:
+    .asciz "hello from libdynamic"
+
+.section .data
+.p2align 2
+.global version
+.type version, @object
+version:
+    .word 3
+.size version, 4
@@ -0,0 +1,47 @@
+# python3 extended.py
+# Rewrites test.elf to use extended section and program header numbering, as a linker does once there are 0xFF00 or
+# more sections, and moves the section index of `_start` into a new `.symtab_shndx` section.
+import struct
+
+elf = bytearray(open("test.elf", "rb").read())
+ph_count, sh_entry_size, sh_count, sh_names = struct.unpack_from("<HHHH", elf, 44)
+sh_offset = struct.unpack_from("<I", elf, 32)[0]
+headers = [bytearray(elf[sh_offset + i * sh_entry_size:][:sh_entry_size]) for i in range(sh_count)]
+elf = elf[:sh_offset]
+
+def field(header, index):
+    return struct.unpack_from("<I", header, index * 4)[0]
+
+def append(data, align=4):
+    elf.extend(b"\0" * (-len(elf) % align))
+    offset = len(elf)
+    elf.extend(data)
+    return offset
+
+# Name the new section by appending to a copy of the section name table
+names = elf[field(headers[sh_names], 4):][:field(headers[sh_names], 5)] + b".symtab_shndx\0"
+struct.pack_into("<II", headers[sh_names], 16, append(names, 1), len(names))
+name = len(names) - len(b".symtab_shndx\0")
+
+symtab = next(i for i, header in enumerate(headers) if field(header, 1) == 2)
+symbols = elf[field(headers[symtab], 4):][:field(headers[symtab], 5)]
+strings = elf[field(headers[field(headers[symtab], 6)], 4):]
+indices = []
+for offset in range(0, len(symbols), 16):
+    symbol = field(headers[symtab], 4) + offset
+    section = struct.unpack_from("<H", elf, symbol + 14)[0]
+    if strings[struct.unpack_from("<I", elf, symbol)[0]:].startswith(b"_start\0"):
+        struct.pack_into("<H", elf, symbol + 14, 0xFFFF)
+        indices.append(section)
+    else:
+        indices.append(0)
+data = append(b"".join(struct.pack("<I", index) for index in indices))
+headers.append(bytearray(struct.pack("<IIIIIIIIII", name, 18, 0, 0, data, len(indices) * 4, symtab, 0, 4, 4)))
+
+# Section header 0 holds the real counts
+struct.pack_into("<I", headers[0], 20, len(headers))
+struct.pack_into("<II", headers[0], 24, sh_names, ph_count)
+sh_offset = append(b"".join(headers))
+struct.pack_into("<I", elf, 32, sh_offset)
+struct.pack_into("<HHHH", elf, 44, 0xFFFF, sh_entry_size, 0, 0xFFFF)
+open("extended.elf", "wb").write(elf)
@@ -0,0 +1,56 @@
+# llvm-mc --triple=riscv32 -mattr=+c,+relax -filetype=obj module.s -o module.o
+.section .text
+.global module_init
+module_init:
+    addi sp, sp, -16
+    sw ra, 12(sp)
+    la a0, message
+    call print
+    lui a1, %hi(counter)
+    lw a2, %lo(counter)(a1)
+    addi a2, a2, 1
+    sw a2, %lo(counter)(a1)
+    beqz a2, 1f
+    j 2f
+1:
+    jal helper
+2:
+    lw ra, 12(sp)
+    addi sp, sp, 16
+    ret
+
+helper:
+    li a0, 0
+    ret
+
+.p2align 2
+.option push
+.option norvc
+.global module_exit
+module_exit:
+    beq a0, zero, helper
+    jal zero, helper
+
+.global module_store
+module_store:
+.Lstore:
+    auipc a1, %pcrel_hi(counter)
+    sw a0, %pcrel_lo(.Lstore)(a1)
+    ret
+.option pop
+
+.section .rodata
+message:
+    .asciz "module loaded\n"
+
+.section .data
+.p2align 2
+.global counter
+counter:
+    .word 0
+table:
+    .word helper, message
+
+.section .bss
+buffer:
+    .zero 64
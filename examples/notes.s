@@ -0,0 +1,19 @@
+# llvm-mc --triple=riscv32 -mattr=+c -filetype=obj notes.s -o notes.o
+# rust-lld -flavor gnu --build-id=sha1 notes.o -o notes.elf
+.section .text
+.global _start
+_start:
+    j _start
+
+# Sizes are literals as label differences become relocations that the linker does not apply before merging notes
+.section .note.ABI-tag, "a", @note
+.balign 4
+    .word 4, 16, 1
+    .string "GNU"
+    .word 0, 6, 1, 0
+
+.section .note.gnu.property, "a", @note
+.balign 4
+    .word 4, 12, 5
+    .string "GNU"
+    .word 0xc0000000, 4, 3
@@ -0,0 +1,23 @@
+# llvm-mc --triple=riscv32 -mattr=+c -filetype=obj pie.s -o pie.o
+# rust-lld -flavor gnu -pie --hash-style=both --allow-shlib-undefined pie.o libdynamic.so -o pie.elf
+.section .text
+.global _start
+_start:
+    li a0, 1
+    li a1, 2
+    call add@plt
+    lla a1, table
+    lw a0, 8(a1)
+    li a7, 93
+    ecall
+
+.section .rodata
+message:
+    .asciz "pie"
+
+.section .data
+.p2align 2
+table:
+    .word _start
+    .word message
+    .word version
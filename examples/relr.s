@@ -0,0 +1,54 @@
+# llvm-mc --triple=riscv32 -mattr=+c -filetype=obj relr.s -o relr.o
+# rust-lld -flavor gnu -pie -z pack-relative-relocs relr.o -o relr.elf
+.section .text
+.global _start
+_start:
+    lla a0, table
+    lw a0, 0(a0)
+    jr a0
+
+.section .data
+.p2align 2
+table:
+    .word _start
+    .word table
+    .word 0
+    .word table + 0
+    .word table + 4
+    .word table + 8
+    .word table + 12
+    .word table + 16
+    .word table + 20
+    .word table + 24
+    .word table + 28
+    .word table + 32
+    .word table + 36
+    .word table + 40
+    .word table + 44
+    .word table + 48
+    .word table + 52
+    .word table + 56
+    .word table + 60
+    .word table + 64
+    .word table + 68
+    .word table + 72
+    .word table + 76
+    .word table + 80
+    .word table + 84
+    .word table + 88
+    .word table + 92
+    .word table + 96
+    .word table + 100
+    .word table + 104
+    .word table + 108
+    .word table + 112
+    .word table + 116
+    .word table + 120
+    .word table + 124
+    .word table + 128
+    .word table + 132
+    .word table + 136
+    .word table + 140
+    .zero 256
+last:
+    .word last
@@ -1,4 +1,5 @@
 # clang --target=riscv32 test.s -nostdlib -o test.elf
+# llvm-objcopy --strip-sections test.elf stripped.elf
 .section .text
 .global _start
 
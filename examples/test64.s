@@ -0,0 +1,20 @@
+# llvm-mc --triple=riscv64 -mattr=+c -filetype=obj test64.s -o test64.o
+# rust-lld -flavor gnu --emit-relocs -Ttext=0x100000000 test64.o -o test64.elf
+.section .text
+.global _start
+.type _start, @function
+_start:
+    la a0, message
+    ld a1, length
+    j _start
+.size _start, . - _start
+
+.section .data
+.global message
+.type message, @object
+message:
+    .string "hello from rv64"
+.size message, . - message
+.balign 8
+length:
+    .dword . - message
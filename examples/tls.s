@@ -0,0 +1,25 @@
+# llvm-mc --triple=riscv32 -mattr=+c,+relax -filetype=obj tls.s -o tls.o
+.section .text
+.global tls_get
+tls_get:
+    lui a0, %tprel_hi(second)
+    add a0, a0, tp, %tprel_add(second)
+    lw a1, %tprel_lo(second)(a0)
+    sw a1, %tprel_lo(second)(a0)
+    # No assembler syntax emits R_RISCV_RVC_LUI, so give it explicitly
+    .reloc ., R_RISCV_RVC_LUI, page
+    c.lui a2, 1
+    .reloc ., R_RISCV_RVC_LUI, low
+    c.lui a3, 1
+    ret
+
+.global page, low
+.set page, 0x1E800
+.set low, 0x7F0
+
+.section .tdata,"awT",@progbits
+.p2align 2
+first:
+    .zero 0x900
+second:
+    .word 2
@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+include!("../parse.rs");
+
+fuzz_target!(|data: &[u8]| parse(data));
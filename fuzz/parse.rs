@@ -0,0 +1,288 @@
+// Drives every public parsing path over arbitrary input. Shared by the fuzz target and the regression test, which
+// both only care that nothing panics: errors are expected and ignored.
+
+use elf_riscv32::*;
+
+/// The address range the loaders may write to.
+const BASE: u32 = 0x10000;
+const SIZE: usize = 0x10000;
+
+pub fn parse(bytes: &[u8]) {
+    parse64(bytes);
+    let elf = match Elf::from_bytes(bytes) {
+        Ok(elf) => elf,
+        Err(_) => return
+    };
+    let _ = format!("{elf:?}");
+    let mut memory = vec![0u8; SIZE];
+
+    if let Ok(programs) = elf.programs() {
+        for program in programs.flatten() {
+            let _ = format!("{program:?}");
+            let address = program.header.virt_addr.get().as_usize().unwrap_or(0) as u32;
+            let _ = elf.segment_data(address, program.header.mem_size.get());
+        }
+    }
+    if let Ok(sections) = elf.sections() {
+        for (index, section) in sections.enumerate() {
+            let Ok(section) = section else { continue };
+            let _ = format!("{section:?}");
+            let _ = elf.section_name(&section);
+            let _ = elf.section(index as u32);
+            match section.header.ty.get() {
+                SectionType::SymbolTable | SectionType::DynamicSymbolTable => symbols(SymbolTable::new(&elf, section)),
+                SectionType::Rela => {
+                    if let Ok(table) = RelaTable::new(&elf, section) {
+                        relocations(&table);
+                    }
+                }
+                SectionType::Rel => {
+                    if let Ok(table) = RelTable::new(&elf, section) {
+                        relocations(&table);
+                    }
+                }
+                SectionType::StringTable => {
+                    if let Ok(strings) = StringTable::new(section) {
+                        for offset in 0..strings.len().min(256) as u32 {
+                            let _ = strings.get_str(offset);
+                        }
+                    }
+                }
+                _ => ()
+            }
+        }
+    }
+    symbols(elf.symbol_table().and_then(|table| table.ok_or(Error::IndexOutOfRange)));
+    symbols(elf.dynamic_symbol_table().and_then(|table| table.ok_or(Error::IndexOutOfRange)));
+    if let Ok(Some(table)) = elf.dynamic_symbol_table() {
+        for symbol in table.symbols().into_iter().flatten().flatten().take(64) {
+            if let Ok(name) = table.name(symbol) {
+                let _ = elf.lookup_symbol(name);
+            }
+        }
+    }
+
+    if let Ok(Some(dynamic)) = elf.dynamic() {
+        let _ = format!("{dynamic:?}");
+        if let Ok(needed) = dynamic.needed() {
+            needed.for_each(drop);
+        }
+        let _ = dynamic.soname();
+        let _ = dynamic.flags();
+        symbols(dynamic.symbols().and_then(|table| table.ok_or(Error::IndexOutOfRange)));
+        let _ = format!("{:?} {:?}", dynamic.hash_table(), dynamic.gnu_hash_table());
+        let _ = dynamic.lookup_symbol("main");
+        let _ = dynamic.rela().map(|table| table.for_each(drop));
+        let _ = dynamic.rel().map(|table| table.for_each(drop));
+        let _ = dynamic.plt_rela().map(|table| table.for_each(drop));
+        let _ = dynamic.plt_rel().map(|table| table.for_each(drop));
+        let _ = dynamic.relr().map(|table| table.for_each(drop));
+        let _ = dynamic.preinit_array();
+        let _ = dynamic.init_array();
+        let _ = dynamic.fini_array();
+        let _ = dynamic.relocate(&mut Region::new(BASE, &mut memory), BASE, |_| Some(BASE));
+    }
+
+    if let Ok(Some(attributes)) = elf.riscv_attributes() {
+        for subsection in attributes.subsections().flatten() {
+            for group in subsection.groups().flatten() {
+                group.indices().for_each(drop);
+                group.attributes().for_each(drop);
+            }
+        }
+        let _ = attributes.arch();
+        let _ = attributes.stack_align();
+        let _ = attributes.unaligned_access();
+        let _ = attributes.priv_spec();
+    }
+    if let Ok(hart) = IsaString::new("rv32gc") {
+        let _ = elf.check_isa(&hart);
+    }
+    let _ = elf.check_abi_compatible(RiscVFlags::Rvc);
+    let _ = elf.build_id();
+    if let Ok(programs) = elf.programs() {
+        for program in programs.flatten() {
+            if let Ok(iter) = NoteIter::from_program(program) {
+                notes(iter);
+            }
+        }
+    }
+    if let Ok(sections) = elf.sections() {
+        for section in sections.flatten() {
+            if let Ok(iter) = NoteIter::from_section(section) {
+                notes(iter);
+            }
+        }
+    }
+    notes(NoteIter::new(bytes, 8));
+    if let Ok(core) = CoreFile::new(elf) {
+        let _ = format!("{core:?}");
+        notes(core.notes());
+        if let Ok(segments) = core.segments() {
+            segments.take(256).for_each(drop);
+        }
+        for thread in core.threads().take(256).flatten() {
+            let _ = (thread.status.pc(), thread.status.register(31), format!("{thread:?}"));
+        }
+        let _ = core.process_info().map(|info| info.map(|info| (info.name(), info.args())));
+        if let Ok(Some(files)) = core.files() {
+            files.take(256).for_each(drop);
+        }
+        let mut buffer = [0; 64];
+        let _ = core.read(BASE, &mut buffer);
+    }
+
+    let allowed = BASE..BASE + SIZE as u32;
+    let _ = Loader::new(&elf).plan(core::slice::from_ref(&allowed));
+    let _ = Loader::new(&elf).load(&mut Region::new(BASE, &mut memory));
+    let _ = Loader::with_bias(&elf, BASE).load(&mut Region::new(BASE, &mut memory));
+
+    let mut addresses = [None; 64];
+    if let Ok(mut loader) = ModuleLoader::new(&elf, &mut addresses) {
+        let mut next = BASE;
+        let placed = loader.place(|_, section| {
+            let address = next;
+            next = next.checked_add(section.header.size.get().min(SIZE as u32)).ok_or(Error::IntegerOverflow)?;
+            Ok(address)
+        });
+        if placed.is_ok() {
+            let mut memory = Region::new(BASE, &mut memory);
+            let _ = loader.load(&mut memory);
+            let _ = loader.relocate(&mut memory, |_| Some(BASE));
+        }
+        let _ = loader.symbol("main");
+    }
+    for address in [ImageAddress::Load, ImageAddress::Run] {
+        let exporter = Exporter::new(&elf, address, Some(0xFF));
+        let _ = exporter.range();
+        let _ = exporter.write_binary(&mut SliceSink::new(&mut memory));
+        let _ = exporter.write_intel_hex(&mut SliceSink::new(&mut memory));
+        let _ = exporter.write_srec(&mut SliceSink::new(&mut memory));
+        // Every block written must read back
+        let uf2 = Uf2Writer::new(&elf, address, Some(Uf2Family::Rp2350RiscV.into()), 0xFF);
+        let mut sink = SliceSink::new(&mut memory);
+        if uf2.write(&mut sink).is_ok() {
+            for block in Uf2Blocks::new(sink.written()) {
+                block.unwrap().payload().unwrap();
+            }
+        }
+    }
+    edit(&elf, bytes);
+}
+
+/// Writing an unmodified file must reproduce it exactly, and an edited file must still parse.
+fn edit(elf: &Elf, bytes: &[u8]) {
+    let mut sections = [None; 64];
+    let mut symbols = [None; 64];
+    let Ok(mut editor) = ElfEditor::new(elf, &mut sections, &mut symbols) else { return };
+    let mut output = vec![0; bytes.len() * 2 + 256];
+    let mut sink = SliceSink::new(&mut output);
+    if editor.write(&mut sink).is_ok() {
+        assert_eq!(sink.written(), bytes);
+    }
+    editor.set_entry(BASE);
+    let _ = editor.set_symbol_value(1, BASE);
+    let _ = editor.remove_section(1);
+    let _ = editor.rename_section(1, b".renamed");
+    let data = SectionData::Bytes(b"extra");
+    let _ = editor.add_section(SectionSpec::new(b".extra", SectionType::Program, SectionFlags::None, data));
+    let mut sink = SliceSink::new(&mut output);
+    if let Ok(size) = editor.write(&mut sink) {
+        assert_eq!(editor.size().ok(), Some(size));
+        assert!(Elf::from_bytes(sink.written()).is_ok());
+    }
+}
+
+/// The paths shared by both classes, run over the input as an ELF64 file.
+fn parse64(bytes: &[u8]) {
+    let Ok(elf) = Elf::<Elf64>::parse(bytes) else { return };
+    let _ = format!("{elf:?}");
+    if let Ok(programs) = elf.programs() {
+        for program in programs.flatten() {
+            let _ = format!("{program:?}");
+            let address = program.header.virt_addr().as_usize().unwrap_or(0) as u64;
+            let _ = elf.segment_data(address, program.header.mem_size());
+        }
+    }
+    if let Ok(sections) = elf.sections() {
+        for (index, section) in sections.enumerate() {
+            let Ok(section) = section else { continue };
+            let _ = format!("{section:?}");
+            let _ = elf.section_name(&section);
+            let _ = elf.section(index as u32);
+            match section.header.ty() {
+                SectionType::SymbolTable | SectionType::DynamicSymbolTable => symbols(SymbolTable::new(&elf, section)),
+                SectionType::Rela => {
+                    if let Ok(table) = RelaTable64::new(&elf, section) {
+                        relocations(&table);
+                    }
+                }
+                SectionType::Rel => {
+                    if let Ok(table) = RelTable64::new(&elf, section) {
+                        relocations(&table);
+                    }
+                }
+                _ => ()
+            }
+        }
+    }
+    if let Ok(Some(attributes)) = elf.riscv_attributes() {
+        let _ = attributes.arch();
+    }
+    if let Ok(hart) = IsaString::new("rv64gc") {
+        let _ = elf.check_isa(&hart);
+    }
+    let _ = elf.check_abi_compatible(RiscVFlags::Rvc);
+}
+
+fn symbols<C: Class>(table: Result<SymbolTable<C>>) {
+    let Ok(table) = table else { return };
+    let _ = format!("{table:?}");
+    if let Ok(symbols) = table.symbols() {
+        for symbol in symbols.flatten() {
+            let _ = table.name(symbol);
+            let _ = (symbol.binding(), symbol.ty(), symbol.visibility(), symbol.section());
+        }
+    }
+    for index in 0..table.len().min(256) {
+        let _ = table.section(index);
+    }
+    let _ = table.get(table.len());
+    let _ = table.find("main");
+
+    // Both symbolizer modes must agree
+    let mut index = [0; 256];
+    if let Ok(sorted) = Symbolizer::with_index(table, &mut index) {
+        let scan = Symbolizer::new(table);
+        for &position in sorted.index().unwrap_or(&[]).iter().take(16) {
+            let Ok(symbol) = table.get(position) else { continue };
+            let address = symbol.value().as_usize().ok().and_then(|value| C::Word::try_from(value as u64).ok());
+            let Some(address) = address else { continue };
+            if let (Ok(sorted), Ok(scan)) = (sorted.symbolize(address), scan.symbolize(address)) {
+                assert_eq!(sorted.map(|found| found.index), scan.map(|found| found.index));
+            }
+        }
+    }
+}
+
+fn notes(notes: NoteIter) {
+    for note in notes.take(256).flatten() {
+        let _ = format!("{note:?}");
+        if let Ok(Some(GnuNote::Properties(properties))) = note.gnu() {
+            for property in properties.take(256).flatten() {
+                let _ = property.as_integer();
+            }
+        }
+    }
+}
+
+fn relocations<'a, R: 'a + Relocation>(table: &RelocationTable<'a, R>) {
+    if let Ok(relocations) = table.relocations() {
+        for relocation in relocations.flatten() {
+            if let (Some(symbols), Ok(symbol)) = (table.symbols, table.symbol(relocation.symbol())) {
+                let _ = symbols.name(symbol);
+            }
+            let _ = (relocation.offset(), relocation.ty(), relocation.addend());
+        }
+    }
+}
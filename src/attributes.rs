@@ -0,0 +1,382 @@
+use core::fmt;
+use crate::{
+    bytes::read_uleb128, Class, Elf, ElfProgramHeader, Error, Program, ProgramType, Result, Section, SectionType
+};
+
+impl<'a, C: Class> Elf<'a, C> {
+    /// Get the RISC-V build attributes, if the file has any.
+    ///
+    /// The `PT_RISCV_ATTRIBUTES` segment is preferred so that files without section headers are still supported.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// # let mut data = [0u32; 256];
+    /// # let elf = include_bytes!("../examples/attributes.elf");
+    /// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+    /// let elf = Elf::new(&data)?;
+    /// let attributes = elf.riscv_attributes()?.unwrap();
+    /// assert!(attributes.arch()?.unwrap().starts_with("rv32i2p0_m2p0_a2p0_f2p0_d2p0_c2p0_zicsr2p0"));
+    /// assert_eq!(attributes.stack_align()?, Some(16));
+    /// assert_eq!(attributes.unaligned_access()?, Some(true));
+    /// assert_eq!(attributes.priv_spec()?, Some(PrivSpec { major: 1, minor: 12, revision: 0 }));
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn riscv_attributes(&self) -> Result<Option<Attributes<'a>>> {
+        for program in self.programs()? {
+            let program = program?;
+            if program.header.ty() == ProgramType::RiscVAttributes {
+                return Attributes::from_program(program).map(Some)
+            }
+        }
+        self.find_section(SectionType::RiscVAttributes)?
+            .map(Attributes::from_section)
+            .transpose()
+    }
+}
+
+/// A view of the build attributes held in a `SHT_RISCV_ATTRIBUTES` section or `PT_RISCV_ATTRIBUTES` segment.
+///
+/// The attributes are split into subsections by vendor, each holding groups of attributes that apply to the whole
+/// file or to a list of sections or symbols. The accessors such as `Attributes::arch` read the file-wide attributes
+/// of the `"riscv"` vendor subsection.
+#[derive(Clone, Copy)]
+pub struct Attributes<'a> {
+    data: &'a [u8]
+}
+impl<'a> Attributes<'a> {
+    /// The format version, which is the first byte of the data.
+    pub const VERSION: u8 = b'A';
+    /// The vendor name of the subsection holding the attributes defined by the RISC-V psABI.
+    pub const RISCV_VENDOR: &'static str = "riscv";
+
+    /// Coerce a `SHT_RISCV_ATTRIBUTES` section into attributes.
+    pub fn from_section<C: Class>(section: Section<'a, C>) -> Result<Self> {
+        section.check_type(SectionType::RiscVAttributes)?;
+        Self::new(section.data)
+    }
+    /// Coerce a `PT_RISCV_ATTRIBUTES` segment into attributes.
+    pub fn from_program<C: Class>(program: Program<'a, C>) -> Result<Self> {
+        program.check_type(ProgramType::RiscVAttributes)?;
+        Self::new(program.data)
+    }
+    /// Create attributes from raw data, which must begin with the format version `'A'`.
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        match data.split_first() {
+            Some((&Self::VERSION, data)) => Ok(Self { data }),
+            Some(_) => Err(Error::InvalidVersion),
+            None => Err(Error::UnexpectedEoF)
+        }
+    }
+    /// Get an iterator over the vendor subsections.
+    pub fn subsections(&self) -> Subsections<'a> {
+        Subsections { data: self.data }
+    }
+    /// Find the subsection for `vendor`.
+    pub fn subsection(&self, vendor: &str) -> Result<Option<Subsection<'a>>> {
+        for subsection in self.subsections() {
+            let subsection = subsection?;
+            if subsection.vendor == vendor {
+                return Ok(Some(subsection))
+            }
+        }
+        Ok(None)
+    }
+    /// Find the file-wide value of `tag` in the `"riscv"` subsection.
+    pub fn get(&self, tag: AttributeTag) -> Result<Option<AttributeValue<'a>>> {
+        let subsection = match self.subsection(Self::RISCV_VENDOR)? {
+            Some(subsection) => subsection,
+            None => return Ok(None)
+        };
+        for group in subsection.groups() {
+            let group = group?;
+            if group.scope != AttributeScope::File {
+                continue
+            }
+            for attribute in group.attributes() {
+                let attribute = attribute?;
+                if attribute.tag == tag {
+                    return Ok(Some(attribute.value))
+                }
+            }
+        }
+        Ok(None)
+    }
+    /// `Tag_RISCV_arch`, the ISA string the file was built for, such as `rv32i2p1_m2p0_a2p1_c2p0_zicsr2p0`.
+    pub fn arch(&self) -> Result<Option<&'a str>> {
+        self.get(AttributeTag::Arch)?.map(AttributeValue::as_str).transpose()
+    }
+    /// `Tag_RISCV_stack_align`, the stack alignment in bytes.
+    pub fn stack_align(&self) -> Result<Option<u32>> {
+        self.get(AttributeTag::StackAlign)?.map(AttributeValue::as_integer).transpose()
+    }
+    /// `Tag_RISCV_unaligned_access`, whether the code may perform unaligned memory accesses.
+    pub fn unaligned_access(&self) -> Result<Option<bool>> {
+        let value = self.get(AttributeTag::UnalignedAccess)?.map(AttributeValue::as_integer).transpose()?;
+        Ok(value.map(|value| value != 0))
+    }
+    /// The privileged specification version from `Tag_RISCV_priv_spec` and its minor and revision tags.
+    ///
+    /// Returns `None` if none of the three are present, otherwise any missing part is 0.
+    pub fn priv_spec(&self) -> Result<Option<PrivSpec>> {
+        let part = |tag| -> Result<Option<u32>> {
+            self.get(tag)?.map(AttributeValue::as_integer).transpose()
+        };
+        let major = part(AttributeTag::PrivSpec)?;
+        let minor = part(AttributeTag::PrivSpecMinor)?;
+        let revision = part(AttributeTag::PrivSpecRevision)?;
+        if major.is_none() && minor.is_none() && revision.is_none() {
+            return Ok(None)
+        }
+        Ok(Some(PrivSpec {
+            major: major.unwrap_or(0),
+            minor: minor.unwrap_or(0),
+            revision: revision.unwrap_or(0)
+        }))
+    }
+}
+impl<'a> fmt::Debug for Attributes<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Attributes")
+            .field("data", &[..])
+            .finish()
+    }
+}
+
+/// A version of the RISC-V privileged specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PrivSpec {
+    pub major: u32,
+    pub minor: u32,
+    pub revision: u32
+}
+
+/// An iterator over the vendor subsections of the attributes.
+#[derive(Debug, Clone)]
+pub struct Subsections<'a> {
+    data: &'a [u8]
+}
+impl<'a> Iterator for Subsections<'a> {
+    type Item = Result<Subsection<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None
+        }
+        let result = (|| {
+            // The length includes the length field itself
+            let (data, rest) = split_length(self.data, 0)?;
+            let end = memchr::memchr(0, data).ok_or(Error::UnterminatedString)?;
+            let vendor = core::str::from_utf8(&data[..end]).map_err(Error::NotUtf8)?;
+            self.data = rest;
+            Ok(Subsection { vendor, data: &data[end + 1..] })
+        })();
+        if result.is_err() {
+            self.data = &[];
+        }
+        Some(result)
+    }
+}
+
+/// The attributes defined by a single vendor.
+#[derive(Clone, Copy)]
+pub struct Subsection<'a> {
+    pub vendor: &'a str,
+    data: &'a [u8]
+}
+impl<'a> Subsection<'a> {
+    /// Get an iterator over the groups of attributes, each applying to the file or to a list of sections or symbols.
+    pub fn groups(&self) -> AttributeGroups<'a> {
+        AttributeGroups { data: self.data }
+    }
+}
+impl<'a> fmt::Debug for Subsection<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subsection")
+            .field("vendor", &self.vendor)
+            .field("data", &[..])
+            .finish()
+    }
+}
+
+/// An iterator over the groups of attributes in a subsection.
+#[derive(Debug, Clone)]
+pub struct AttributeGroups<'a> {
+    data: &'a [u8]
+}
+impl<'a> Iterator for AttributeGroups<'a> {
+    type Item = Result<AttributeGroup<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None
+        }
+        let result = (|| {
+            let (scope, length) = read_uleb128(self.data)?;
+            let scope = AttributeScope(scope);
+            scope.validate()?;
+            // The length includes the tag and the length field itself
+            let (data, rest) = split_length(self.data, length)?;
+            self.data = rest;
+            // Section and symbol groups begin with a list of indices terminated by 0
+            let mut indices = &data[..0];
+            let mut data = data;
+            if scope != AttributeScope::File {
+                let mut end = 0;
+                loop {
+                    let (index, length) = read_uleb128(&data[end..])?;
+                    end += length;
+                    if index == 0 {
+                        break
+                    }
+                }
+                indices = &data[..end];
+                data = &data[end..];
+            }
+            Ok(AttributeGroup { scope, indices, data })
+        })();
+        if result.is_err() {
+            self.data = &[];
+        }
+        Some(result)
+    }
+}
+
+/// A group of attributes and what they apply to.
+#[derive(Clone, Copy)]
+pub struct AttributeGroup<'a> {
+    pub scope: AttributeScope,
+    indices: &'a [u8],
+    data: &'a [u8]
+}
+impl<'a> AttributeGroup<'a> {
+    /// Get an iterator over the section or symbol indices the attributes apply to, which is empty for `Tag_File`.
+    pub fn indices(&self) -> impl Iterator<Item = Result<u32>> + 'a {
+        let mut data = self.indices;
+        core::iter::from_fn(move || {
+            if data.is_empty() {
+                return None
+            }
+            match read_uleb128(data) {
+                Ok((0, _)) => {
+                    data = &[];
+                    None
+                }
+                Ok((index, length)) => {
+                    data = &data[length..];
+                    Some(Ok(index))
+                }
+                Err(error) => {
+                    data = &[];
+                    Some(Err(error))
+                }
+            }
+        })
+    }
+    /// Get an iterator over the attributes in the group.
+    pub fn attributes(&self) -> AttributeIter<'a> {
+        AttributeIter { data: self.data }
+    }
+}
+impl<'a> fmt::Debug for AttributeGroup<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttributeGroup")
+            .field("scope", &self.scope)
+            .field("indices", &[..])
+            .field("data", &[..])
+            .finish()
+    }
+}
+
+/// An iterator over the attributes in a group.
+#[derive(Debug, Clone)]
+pub struct AttributeIter<'a> {
+    data: &'a [u8]
+}
+impl<'a> Iterator for AttributeIter<'a> {
+    type Item = Result<Attribute<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None
+        }
+        let result = (|| {
+            let (tag, length) = read_uleb128(self.data)?;
+            let data = &self.data[length..];
+            // Unknown tags can still be skipped: odd tags hold strings and even tags hold integers
+            let (value, length) = if tag & 1 == 1 {
+                let end = memchr::memchr(0, data).ok_or(Error::UnterminatedString)?;
+                let value = core::str::from_utf8(&data[..end]).map_err(Error::NotUtf8)?;
+                (AttributeValue::String(value), end + 1)
+            } else {
+                let (value, length) = read_uleb128(data)?;
+                (AttributeValue::Integer(value), length)
+            };
+            self.data = &data[length..];
+            Ok(Attribute { tag: AttributeTag(tag), value })
+        })();
+        if result.is_err() {
+            self.data = &[];
+        }
+        Some(result)
+    }
+}
+
+/// A single build attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Attribute<'a> {
+    pub tag: AttributeTag,
+    pub value: AttributeValue<'a>
+}
+
+/// The value of an attribute, which is a string for odd tags and an integer for even tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeValue<'a> {
+    Integer(u32),
+    String(&'a str)
+}
+impl<'a> AttributeValue<'a> {
+    pub fn as_integer(self) -> Result<u32> {
+        match self {
+            Self::Integer(value) => Ok(value),
+            Self::String(_) => Err(Error::InvalidFormat)
+        }
+    }
+    pub fn as_str(self) -> Result<&'a str> {
+        match self {
+            Self::String(value) => Ok(value),
+            Self::Integer(_) => Err(Error::InvalidFormat)
+        }
+    }
+}
+
+/// Split `data` at the little-endian word `offset` bytes in, which gives the length of the block starting at `data`.
+fn split_length(data: &[u8], offset: usize) -> Result<(&[u8], &[u8])> {
+    let length = data.get(offset..offset + 4).ok_or(Error::UnexpectedEoF)?;
+    let length = u32::from_le_bytes([length[0], length[1], length[2], length[3]]) as usize;
+    if length < offset + 4 {
+        return Err(Error::InvalidFormat)
+    }
+    if length > data.len() {
+        return Err(Error::UnexpectedEoF)
+    }
+    let (block, rest) = data.split_at(length);
+    Ok((&block[offset + 4..], rest))
+}
+
+c_enum!{
+    pub AttributeScope(u32) {
+        File = 1,
+        Section = 2,
+        Symbol = 3
+    } v => Err(Error::UnsupportedAttributeScope(Self(v)))
+}
+c_enum!{
+    pub AttributeTag(u32) {
+        StackAlign = 4,
+        Arch = 5,
+        UnalignedAccess = 6,
+        PrivSpec = 8,
+        PrivSpecMinor = 10,
+        PrivSpecRevision = 12,
+        AtomicAbi = 14,
+        X3RegUsage = 16
+    } v => Err(Error::UnsupportedAttributeTag(Self(v)))
+}
@@ -0,0 +1,548 @@
+use core::{mem::{size_of, size_of_val}, ops::Range};
+use crate::{
+    Address, Error, FileType, Header, Machine, Offset, ProgramFlags, ProgramHeader, ProgramType, Rela, Result,
+    RiscVFlags, SectionFlags, SectionHeader, SectionType, Sink, Symbol, SymbolBinding, SymbolSection, SymbolType,
+    SymbolVisibility, View
+};
+
+/// Lays out and writes a new elf32-littleriscv file, without allocating.
+///
+/// Sections are described by `sections` and are numbered from 1 in the order given, after the null section. A
+/// `.strtab` for the names of the symbols and a `.shstrtab` for the names of the sections are appended after them, and
+/// `section_name_table` is set to the last. The file offset of each section is chosen so that sections in the same
+/// `PT_LOAD` segment keep the distance between their addresses, and the first is congruent to its address modulo the
+/// segment alignment. The section header table is placed at the end of the file.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// // lui a0, 0x20; addi a0, a0, 0; ret
+/// let text = [0x37, 0x05, 0x02, 0x00, 0x13, 0x05, 0x05, 0x00, 0x67, 0x80, 0x00, 0x00];
+/// let data = *b"hello\0\0\0";
+/// let symbols = [
+///     SymbolSpec::new(b"message", SymbolBinding::Local, SymbolType::Object, SymbolSection::Index(2), 0x20000, 6),
+///     SymbolSpec::new(b"_start", SymbolBinding::Global, SymbolType::Function, SymbolSection::Index(1), 0x10000, 12)
+/// ];
+/// let sections = [
+///     SectionSpec::program(b".text", SectionFlags::Alloc | SectionFlags::Exec, 0x10000, 4, &text),
+///     SectionSpec::program(b".data", SectionFlags::Alloc | SectionFlags::Write, 0x20000, 4, &data),
+///     SectionSpec::no_bits(b".bss", SectionFlags::Alloc | SectionFlags::Write, 0x20008, 8, 0x100),
+///     SectionSpec::symbols(&symbols)
+/// ];
+/// let segments = [
+///     SegmentSpec::new(ProgramType::Load, ProgramFlags::Read | ProgramFlags::Exec, 1..2, 0x1000),
+///     SegmentSpec::new(ProgramType::Load, ProgramFlags::Read | ProgramFlags::Write, 2..4, 0x1000)
+/// ];
+/// let builder = ElfBuilder { entry: 0x10000, ..ElfBuilder::new(FileType::Executable, &sections, &segments) };
+///
+/// let mut buffer = [0; 0x3000];
+/// let mut sink = SliceSink::new(&mut buffer);
+/// builder.write(&mut sink)?;
+/// assert_eq!(sink.len(), builder.size()?);
+///
+/// let elf = Elf::from_bytes(sink.written())?;
+/// assert_eq!(elf.header.entry.get().as_usize()?, 0x10000);
+/// assert_eq!(elf.segment_data(0x10000, 12)?, &text);
+/// let bss = elf.programs()?.nth(1).unwrap()?;
+/// assert_eq!((bss.header.file_size.get(), bss.header.mem_size.get()), (8, 0x108));
+/// let symbols = elf.symbol_table()?.unwrap();
+/// let (_, start) = symbols.find("_start")?.unwrap();
+/// assert_eq!((start.binding(), start.section()), (SymbolBinding::Global, SymbolSection::Index(1)));
+/// let names = elf.sections()?.flatten().map(|section| elf.section_name(&section)).collect::<Result<Vec<_>>>()?;
+/// assert_eq!(names, ["", ".text", ".data", ".bss", ".symtab", ".strtab", ".shstrtab"]);
+/// # Ok(()) })().unwrap()
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ElfBuilder<'a> {
+    pub ty: FileType,
+    pub entry: u32,
+    pub flags: RiscVFlags,
+    pub sections: &'a [SectionSpec<'a>],
+    pub segments: &'a [SegmentSpec]
+}
+impl<'a> ElfBuilder<'a> {
+    pub fn new(ty: FileType, sections: &'a [SectionSpec<'a>], segments: &'a [SegmentSpec]) -> Self {
+        Self {
+            ty,
+            entry: 0,
+            flags: RiscVFlags::None,
+            sections,
+            segments
+        }
+    }
+    /// Returns the size of the file in bytes.
+    pub fn size(&self) -> Result<usize> {
+        let end = self.layout(|_, _, _| Ok(()))?;
+        Ok(self.section_table_offset(end)? + self.section_count() * size_of::<SectionHeader>())
+    }
+    /// Write the file to `sink`, returning its size in bytes.
+    pub fn write(&self, sink: &mut impl Sink) -> Result<usize> {
+        let programs_size = self.segments.len() * size_of::<ProgramHeader>();
+        let end = self.layout(|_, _, _| Ok(()))?;
+        let section_table = self.section_table_offset(end)?;
+        let header = Header {
+            ident: *b"\x7fELF\x01\x01\x01\0\0\0\0\0\0\0\0\0",
+            ty: self.ty.into(),
+            machine: Machine::RiscV.into(),
+            version: 1.into(),
+            entry: Address(self.entry).into(),
+            ph_offset: Offset(if self.segments.is_empty() { 0 } else { size_of::<Header>() as u32 }).into(),
+            sh_offset: Offset(to_u32(section_table)?).into(),
+            flags: self.flags.0.into(),
+            header_size: (size_of::<Header>() as u16).into(),
+            ph_entry_size: (size_of::<ProgramHeader>() as u16).into(),
+            ph_count: u16::try_from(self.segments.len()).map_err(|_| Error::IntegerOverflow)?.into(),
+            sh_entry_size: (size_of::<SectionHeader>() as u16).into(),
+            sh_count: u16::try_from(self.section_count()).map_err(|_| Error::IntegerOverflow)?.into(),
+            section_name_table: u16::try_from(self.section_count() - 1).map_err(|_| Error::IntegerOverflow)?.into()
+        };
+        sink.write(header.as_bytes())?;
+        for segment in self.segments {
+            sink.write(self.program_header(segment)?.as_bytes())?;
+        }
+
+        let mut position = size_of::<Header>() + programs_size;
+        self.layout(|_, entry, offset| {
+            sink.zeros(offset - position)?;
+            position = offset + self.file_size(entry);
+            self.write_content(sink, entry)
+        })?;
+        sink.zeros(section_table - position)?;
+
+        sink.zeros(size_of::<SectionHeader>())?;
+        let mut name = 1;
+        self.layout(|_, entry, offset| {
+            let header = SectionHeader {
+                name: to_u32(name)?.into(),
+                ty: entry.ty.into(),
+                flags: entry.flags.into(),
+                address: Address(entry.address).into(),
+                offset: Offset(to_u32(offset)?).into(),
+                size: to_u32(self.mem_size(entry))?.into(),
+                link: entry.link.into(),
+                info: entry.info.into(),
+                alignment: entry.alignment.into(),
+                entry_size: entry.entry_size.into()
+            };
+            name += entry.name.len() + 1;
+            sink.write(header.as_bytes())
+        })?;
+        Ok(section_table + self.section_count() * size_of::<SectionHeader>())
+    }
+
+    /// The number of sections including the null section and the generated string tables.
+    fn section_count(&self) -> usize {
+        self.sections.len() + usize::from(self.symbol_table().is_some()) + 2
+    }
+    /// The index of the symbol table, if there is one.
+    fn symbol_table(&self) -> Option<usize> {
+        self.sections.iter().position(|section| matches!(section.data, SectionData::Symbols(_))).map(|index| index + 1)
+    }
+    fn entry(&self, index: usize) -> Entry<'a> {
+        let strings = self.sections.len() + 1;
+        if let Some(section) = index.checked_sub(1).and_then(|index| self.sections.get(index)) {
+            let mut entry = Entry {
+                name: section.name,
+                ty: section.ty,
+                flags: section.flags,
+                address: section.address,
+                alignment: section.alignment,
+                link: section.link,
+                info: section.info,
+                entry_size: section.entry_size,
+                content: Content::Data(section.data)
+            };
+            match section.data {
+                SectionData::Symbols(symbols) => {
+                    entry.ty = SectionType::SymbolTable;
+                    entry.link = strings as u32;
+                    let locals = symbols.iter().take_while(|symbol| symbol.binding == SymbolBinding::Local).count();
+                    entry.info = locals as u32 + 1;
+                    entry.entry_size = size_of::<Symbol>() as u32;
+                }
+                SectionData::Relocations(_) => {
+                    entry.ty = SectionType::Rela;
+                    entry.link = self.symbol_table().unwrap_or(0) as u32;
+                    entry.entry_size = size_of::<Rela>() as u32;
+                }
+                _ => ()
+            }
+            entry
+        } else {
+            let symbols = self.symbol_table().and_then(|index| match self.sections[index - 1].data {
+                SectionData::Symbols(symbols) => Some(symbols),
+                _ => None
+            });
+            let (name, content) = match symbols {
+                Some(symbols) if index == strings => (&b".strtab"[..], Content::SymbolNames(symbols)),
+                _ => (&b".shstrtab"[..], Content::SectionNames)
+            };
+            Entry {
+                name,
+                ty: SectionType::StringTable,
+                flags: SectionFlags::None,
+                address: 0,
+                alignment: 1,
+                link: 0,
+                info: 0,
+                entry_size: 0,
+                content
+            }
+        }
+    }
+    fn file_size(&self, entry: &Entry) -> usize {
+        match entry.content {
+            Content::Data(SectionData::NoBits(_)) => 0,
+            _ => self.mem_size(entry)
+        }
+    }
+    fn mem_size(&self, entry: &Entry) -> usize {
+        match entry.content {
+            Content::Data(SectionData::Bytes(data)) => data.len(),
+            Content::Data(SectionData::NoBits(size)) => size as usize,
+            Content::Data(SectionData::Symbols(symbols)) => (symbols.len() + 1) * size_of::<Symbol>(),
+            Content::Data(SectionData::Relocations(relocations)) => size_of_val(relocations),
+            Content::SymbolNames(symbols) => symbols.iter().map(|symbol| symbol.name.len() + 1).sum::<usize>() + 1,
+            Content::SectionNames => {
+                (1..self.section_count()).map(|index| self.entry(index).name.len() + 1).sum::<usize>() + 1
+            }
+        }
+    }
+    fn write_content(&self, sink: &mut impl Sink, entry: &Entry) -> Result<()> {
+        match entry.content {
+            Content::Data(SectionData::Bytes(data)) => sink.write(data),
+            Content::Data(SectionData::NoBits(_)) => Ok(()),
+            Content::Data(SectionData::Symbols(symbols)) => {
+                sink.zeros(size_of::<Symbol>())?;
+                let mut name = 1;
+                for symbol in symbols {
+                    let entry = Symbol {
+                        name: to_u32(name)?.into(),
+                        value: Address(symbol.value).into(),
+                        size: symbol.size.into(),
+                        info: symbol.binding.0 << 4 | symbol.ty.0 & 0xF,
+                        other: symbol.visibility.0,
+                        section_index: section_index(symbol.section)?.into()
+                    };
+                    name += symbol.name.len() + 1;
+                    sink.write(entry.as_bytes())?;
+                }
+                Ok(())
+            }
+            Content::Data(SectionData::Relocations(relocations)) => {
+                relocations.iter().try_for_each(|relocation| sink.write(relocation.as_bytes()))
+            }
+            Content::SymbolNames(symbols) => {
+                sink.write(&[0])?;
+                symbols.iter().try_for_each(|symbol| {
+                    sink.write(symbol.name)?;
+                    sink.write(&[0])
+                })
+            }
+            Content::SectionNames => {
+                sink.write(&[0])?;
+                (1..self.section_count()).try_for_each(|index| {
+                    sink.write(self.entry(index).name)?;
+                    sink.write(&[0])
+                })
+            }
+        }
+    }
+    /// The segment that decides the file offset of a section, preferring `PT_LOAD` segments.
+    fn segment_of(&self, index: usize) -> Option<&SegmentSpec> {
+        let contains = |segment: &&SegmentSpec| segment.sections.contains(&(index as u32));
+        let mut segments = self.segments.iter().filter(contains);
+        segments.clone().find(|segment| segment.ty == ProgramType::Load).or_else(|| segments.next())
+    }
+    /// Call `place` with the index, description and file offset of each section after the null section, returning
+    /// the end of the section data.
+    fn layout(&self, mut place: impl FnMut(usize, &Entry<'a>, usize) -> Result<()>) -> Result<usize> {
+        let mut symbol_tables = self.sections.iter().filter_map(|section| match section.data {
+            SectionData::Symbols(symbols) => Some(symbols),
+            _ => None
+        });
+        if let Some(symbols) = symbol_tables.next() {
+            if symbol_tables.next().is_some() {
+                return Err(Error::InvalidFormat)
+            }
+            let locals = symbols.iter().take_while(|symbol| symbol.binding == SymbolBinding::Local).count();
+            if symbols[locals..].iter().any(|symbol| symbol.binding == SymbolBinding::Local) {
+                return Err(Error::LocalSymbolAfterGlobal)
+            }
+        }
+        for segment in self.segments {
+            let past_end = segment.sections.end as usize > self.sections.len() + 1;
+            if past_end || segment.sections.start == 0 && !segment.sections.is_empty() {
+                return Err(Error::IndexOutOfRange)
+            }
+        }
+
+        let mut cursor = size_of::<Header>() + self.segments.len() * size_of::<ProgramHeader>();
+        // The offset and address of the first section in the current segment
+        let mut base = (0, 0);
+        for index in 1..self.section_count() {
+            let entry = self.entry(index);
+            let alignment = power_of_two(entry.alignment)?;
+            let segment = self.segment_of(index).filter(|_| entry.flags.any(SectionFlags::Alloc));
+            let offset = match segment {
+                Some(segment) if segment.sections.start as usize != index => {
+                    let overlap = Error::SegmentOverlap { address: entry.address };
+                    let distance = entry.address.checked_sub(base.1).ok_or(overlap)?;
+                    let offset = base.0 + distance as usize;
+                    if offset < cursor {
+                        return Err(Error::SegmentOverlap { address: entry.address })
+                    }
+                    offset
+                }
+                Some(segment) => {
+                    let alignment = alignment.max(power_of_two(segment.align)?);
+                    let offset = cursor + ((entry.address as usize).wrapping_sub(cursor) & (alignment - 1));
+                    base = (offset, entry.address);
+                    offset
+                }
+                None => cursor.checked_add(alignment - 1).ok_or(Error::IntegerOverflow)? & !(alignment - 1)
+            };
+            place(index, &entry, offset)?;
+            cursor = offset.checked_add(self.file_size(&entry)).ok_or(Error::IntegerOverflow)?;
+        }
+        Ok(cursor)
+    }
+    fn section_table_offset(&self, end: usize) -> Result<usize> {
+        Ok(end.checked_add(3).ok_or(Error::IntegerOverflow)? & !3)
+    }
+    fn program_header(&self, segment: &SegmentSpec) -> Result<ProgramHeader> {
+        // The offset and address of the first section, and the end of the file data and memory image
+        let mut bounds: Option<(usize, u32, usize, u64)> = None;
+        self.layout(|index, entry, offset| {
+            if segment.sections.contains(&(index as u32)) {
+                let file_end = offset + self.file_size(entry);
+                let mem_end = u64::from(entry.address) + self.mem_size(entry) as u64;
+                let (_, _, file, mem) = bounds.get_or_insert((offset, entry.address, offset, mem_end));
+                if self.file_size(entry) != 0 {
+                    *file = file_end;
+                }
+                *mem = mem_end.max(*mem);
+            }
+            Ok(())
+        })?;
+        let (offset, address, file_end, mem_end) = bounds.unwrap_or_default();
+        Ok(ProgramHeader {
+            ty: segment.ty.into(),
+            offset: Offset(to_u32(offset)?).into(),
+            virt_addr: Address(address).into(),
+            phys_addr: Address(address).into(),
+            file_size: to_u32(file_end - offset)?.into(),
+            mem_size: u32::try_from(mem_end - u64::from(address)).map_err(|_| Error::IntegerOverflow)?.into(),
+            flags: segment.flags.into(),
+            align: segment.align.into()
+        })
+    }
+}
+
+/// A section to be written by an [`ElfBuilder`].
+///
+/// The type, link, info and entry size of symbol tables and relocation sections are filled in by the builder.
+#[derive(Debug, Clone, Copy)]
+pub struct SectionSpec<'a> {
+    pub name: &'a [u8],
+    pub ty: SectionType,
+    pub flags: SectionFlags,
+    pub address: u32,
+    /// The required alignment of the address and file offset, which must be 0 or a power of 2.
+    pub alignment: u32,
+    pub link: u32,
+    pub info: u32,
+    pub entry_size: u32,
+    pub data: SectionData<'a>
+}
+impl<'a> SectionSpec<'a> {
+    /// Create an unaligned section at address 0.
+    pub fn new(name: &'a [u8], ty: SectionType, flags: SectionFlags, data: SectionData<'a>) -> Self {
+        Self {
+            name,
+            ty,
+            flags,
+            address: 0,
+            alignment: 1,
+            link: 0,
+            info: 0,
+            entry_size: 0,
+            data
+        }
+    }
+    /// Create a `SHT_PROGBITS` section, such as `.text` or `.data`.
+    pub fn program(name: &'a [u8], flags: SectionFlags, address: u32, alignment: u32, data: &'a [u8]) -> Self {
+        Self {
+            address,
+            alignment,
+            ..Self::new(name, SectionType::Program, flags, SectionData::Bytes(data))
+        }
+    }
+    /// Create a `SHT_NOBITS` section of `size` zero bytes that takes no space in the file, such as `.bss`.
+    pub fn no_bits(name: &'a [u8], flags: SectionFlags, address: u32, alignment: u32, size: u32) -> Self {
+        Self {
+            address,
+            alignment,
+            ..Self::new(name, SectionType::NoBits, flags, SectionData::NoBits(size))
+        }
+    }
+    /// Create the `.symtab` section.
+    ///
+    /// The symbols are numbered from 1 in the order given, after the null symbol, and local symbols must come first.
+    /// Only one symbol table may be written.
+    pub fn symbols(symbols: &'a [SymbolSpec<'a>]) -> Self {
+        Self {
+            alignment: 4,
+            ..Self::new(b".symtab", SectionType::SymbolTable, SectionFlags::None, SectionData::Symbols(symbols))
+        }
+    }
+    /// Create a `SHT_RELA` section holding relocations against the section at index `target`, using the symbol
+    /// table.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// let text = [0; 8];
+    /// let puts = SymbolSpec::new(b"puts", SymbolBinding::Global, SymbolType::NoType, SymbolSection::Undefined, 0, 0);
+    /// let symbols = [puts];
+    /// let relocations = [Rela::from_parts(0, 1, RelocationType::Call, 0)?];
+    /// assert!(matches!(Rela::from_parts(0, 1 << 24, RelocationType::Call, 0), Err(Error::IndexOutOfRange)));
+    /// let sections = [
+    ///     SectionSpec::program(b".text", SectionFlags::Alloc | SectionFlags::Exec, 0, 4, &text),
+    ///     SectionSpec::symbols(&symbols),
+    ///     SectionSpec::relocations(b".rela.text", 1, &relocations)
+    /// ];
+    /// let mut buffer = [0; 512];
+    /// let mut sink = SliceSink::new(&mut buffer);
+    /// ElfBuilder::new(FileType::Relocatable, &sections, &[]).write(&mut sink)?;
+    ///
+    /// let elf = Elf::from_bytes(sink.written())?;
+    /// let rela = RelaTable::new(&elf, elf.find_section(SectionType::Rela)?.unwrap())?;
+    /// let relocation = rela.relocations()?.next().unwrap()?;
+    /// assert_eq!((relocation.ty(), relocation.symbol()), (RelocationType::Call, 1));
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn relocations(name: &'a [u8], target: u32, relocations: &'a [Rela]) -> Self {
+        Self {
+            alignment: 4,
+            info: target,
+            ..Self::new(name, SectionType::Rela, SectionFlags::InfoLink, SectionData::Relocations(relocations))
+        }
+    }
+}
+
+/// The contents of a [`SectionSpec`].
+#[derive(Debug, Clone, Copy)]
+pub enum SectionData<'a> {
+    /// Raw bytes copied into the file.
+    Bytes(&'a [u8]),
+    /// A size in memory with no bytes in the file.
+    NoBits(u32),
+    /// Symbols to be written as a symbol table, with their names in a generated `.strtab`.
+    Symbols(&'a [SymbolSpec<'a>]),
+    /// Relocations with addends referring to the symbol table.
+    Relocations(&'a [Rela])
+}
+
+/// A symbol to be written by an [`ElfBuilder`].
+#[derive(Debug, Clone, Copy)]
+pub struct SymbolSpec<'a> {
+    pub name: &'a [u8],
+    pub binding: SymbolBinding,
+    pub ty: SymbolType,
+    pub visibility: SymbolVisibility,
+    pub section: SymbolSection,
+    pub value: u32,
+    pub size: u32
+}
+impl<'a> SymbolSpec<'a> {
+    /// Create a symbol with default visibility.
+    pub fn new(
+        name: &'a [u8],
+        binding: SymbolBinding,
+        ty: SymbolType,
+        section: SymbolSection,
+        value: u32,
+        size: u32
+    ) -> Self {
+        Self {
+            name,
+            binding,
+            ty,
+            visibility: SymbolVisibility::Default,
+            section,
+            value,
+            size
+        }
+    }
+}
+
+/// A segment to be written by an [`ElfBuilder`], covering a range of section indices.
+///
+/// The offset, addresses and sizes are taken from the sections. A segment with no sections, such as
+/// `PT_GNU_STACK`, is empty.
+#[derive(Debug, Clone)]
+pub struct SegmentSpec {
+    pub ty: ProgramType,
+    pub flags: ProgramFlags,
+    pub sections: Range<u32>,
+    /// The alignment of the segment, which must be 0 or a power of 2, usually the page size for `PT_LOAD`.
+    pub align: u32
+}
+impl SegmentSpec {
+    pub fn new(ty: ProgramType, flags: ProgramFlags, sections: Range<u32>, align: u32) -> Self {
+        Self {
+            ty,
+            flags,
+            sections,
+            align
+        }
+    }
+}
+
+/// A section to be written, including the generated string tables.
+struct Entry<'a> {
+    name: &'a [u8],
+    ty: SectionType,
+    flags: SectionFlags,
+    address: u32,
+    alignment: u32,
+    link: u32,
+    info: u32,
+    entry_size: u32,
+    content: Content<'a>
+}
+
+#[derive(Clone, Copy)]
+enum Content<'a> {
+    Data(SectionData<'a>),
+    SymbolNames(&'a [SymbolSpec<'a>]),
+    SectionNames
+}
+
+fn to_u32(value: usize) -> Result<u32> {
+    u32::try_from(value).map_err(|_| Error::IntegerOverflow)
+}
+
+/// Returns the alignment as a power of 2, treating 0 as 1.
+fn power_of_two(alignment: u32) -> Result<usize> {
+    match alignment {
+        0 => Ok(1),
+        alignment if alignment.is_power_of_two() => Ok(alignment as usize),
+        _ => Err(Error::Unaligned)
+    }
+}
+
+/// Encode the `st_shndx` of a symbol.
+fn section_index(section: SymbolSection) -> Result<u16> {
+    match section {
+        SymbolSection::Undefined => Ok(0),
+        SymbolSection::Absolute => Ok(0xFFF1),
+        SymbolSection::Common => Ok(0xFFF2),
+        SymbolSection::Extended => Ok(0xFFFF),
+        SymbolSection::Reserved(index) => Ok(index),
+        SymbolSection::Index(index) => {
+            u16::try_from(index).ok().filter(|&index| index < 0xFF00).ok_or(Error::IndexOutOfRange)
+        }
+    }
+}
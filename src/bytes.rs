@@ -0,0 +1,23 @@
+use crate::{Error, Result};
+
+/// Read the `index`th little-endian word of `data`.
+pub(crate) fn read_u32(data: &[u8], index: u32) -> Result<u32> {
+    let start = (index as usize).checked_mul(4).ok_or(Error::IntegerOverflow)?;
+    let end = start.checked_add(4).ok_or(Error::IntegerOverflow)?;
+    data.get(start..end)
+        .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+        .ok_or(Error::UnexpectedEoF)
+}
+
+/// Decode the ULEB128 value at the start of `data`, which must fit in 32 bits, returning it with its encoded length.
+pub(crate) fn read_uleb128(data: &[u8]) -> Result<(u32, usize)> {
+    let mut value = 0u32;
+    for i in 0..5 {
+        let byte = *data.get(i).ok_or(Error::UnexpectedEoF)?;
+        value |= ((byte & 0x7F) as u32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1))
+        }
+    }
+    Err(Error::IntegerOverflow)
+}
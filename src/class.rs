@@ -0,0 +1,269 @@
+//! The two ELF classes, which share a format but lay out their structures with different field widths and orders.
+//!
+//! Each on-disk structure has a 32-bit and a 64-bit view, e.g. `SectionHeader` and `SectionHeader64`, with the same
+//! field names. Code that works with either class is generic over `Class` and reads fields through the traits in this
+//! module, which widen them to the class's `Word`.
+
+use core::fmt;
+use crate::{
+    Address, Endian, Error, FileType, Machine, Offset, ProgramFlags, ProgramType, Result, RiscVFlags, SectionFlags,
+    SectionType, SymbolBinding, SymbolSection, SymbolType, SymbolVisibility, View
+};
+
+/// An ELF class, selecting the layout of the file's structures.
+pub trait Class: Copy + fmt::Debug + 'static {
+    /// The value of `ident[4]` (`EI_CLASS`) for files of this class.
+    const CLASS: u8;
+    /// The width of addresses, offsets and sizes.
+    type Word: Word;
+    /// The signed counterpart of `Word`, used for relocation addends.
+    type Sword: Endian + fmt::Debug;
+    type Header: ElfHeader<Self>;
+    type ProgramHeader: ElfProgramHeader<Self>;
+    type SectionHeader: ElfSectionHeader<Self>;
+    type Symbol: ElfSymbol<Self>;
+    /// Split `r_info` into the symbol index and relocation type.
+    fn split_info(info: Self::Word) -> (u32, u32);
+}
+
+/// `ELFCLASS32`, used by RV32 targets.
+#[derive(Debug, Clone, Copy)]
+pub enum Elf32 {}
+impl Class for Elf32 {
+    const CLASS: u8 = 1;
+    type Word = u32;
+    type Sword = i32;
+    type Header = crate::Header;
+    type ProgramHeader = crate::ProgramHeader;
+    type SectionHeader = crate::SectionHeader;
+    type Symbol = crate::Symbol;
+    fn split_info(info: u32) -> (u32, u32) {
+        (info >> 8, info & 0xFF)
+    }
+}
+
+/// `ELFCLASS64`, used by RV64 targets.
+#[derive(Debug, Clone, Copy)]
+pub enum Elf64 {}
+impl Class for Elf64 {
+    const CLASS: u8 = 2;
+    type Word = u64;
+    type Sword = i64;
+    type Header = crate::Header64;
+    type ProgramHeader = crate::ProgramHeader64;
+    type SectionHeader = crate::SectionHeader64;
+    type Symbol = crate::Symbol64;
+    fn split_info(info: u64) -> (u32, u32) {
+        ((info >> 32) as u32, info as u32)
+    }
+}
+
+/// An unsigned address-sized integer, `u32` or `u64`.
+pub trait Word:
+    Endian + Copy + Ord + Default + fmt::Debug + fmt::UpperHex + fmt::LowerHex + From<u32> + Into<u64> + TryFrom<u64>
+{
+    /// Convert to a `usize`, failing if it does not fit on the host.
+    fn as_usize(self) -> Result<usize> {
+        let value: u64 = self.into();
+        value.try_into().map_err(|_| Error::IntegerOverflow)
+    }
+    /// Convert to a `u32`, failing if it does not fit.
+    fn as_u32(self) -> Result<u32> {
+        let value: u64 = self.into();
+        value.try_into().map_err(|_| Error::IntegerOverflow)
+    }
+}
+impl Word for u32 {}
+impl Word for u64 {}
+
+/// Implement accessors that decode the field of the same name.
+macro_rules! fields {
+    ($($field:ident: $ty:ty),*) => {
+        $(fn $field(&self) -> $ty {
+            self.$field.get()
+        })*
+    };
+}
+
+/// The fields of `Header` and `Header64`, along with the operations built on them.
+pub trait ElfHeader<C: Class>: View + fmt::Debug {
+    fn ident(&self) -> &[u8; 16];
+    fn ty(&self) -> FileType;
+    fn machine(&self) -> Machine;
+    fn version(&self) -> u32;
+    fn entry(&self) -> Address<C::Word>;
+    fn ph_offset(&self) -> Offset<C::Word>;
+    fn sh_offset(&self) -> Offset<C::Word>;
+    fn flags(&self) -> u32;
+    fn header_size(&self) -> u16;
+    fn ph_entry_size(&self) -> u16;
+    fn ph_count(&self) -> u16;
+    fn sh_entry_size(&self) -> u16;
+    fn sh_count(&self) -> u16;
+    fn section_name_table(&self) -> u16;
+
+    /// Coerce a byte slice of any alignment into an ELF header of this class.
+    ///
+    /// Returns `Error::WrongClass` if the file is of the other class.
+    fn from_bytes(elf: &[u8]) -> Result<&Self> {
+        let elf = Self::view(elf)?;
+        let ident = elf.ident();
+        if &ident[..4] != b"\x7fELF" {
+            return Err(Error::InvalidMagic)
+        }
+        if ident[5] != 1 {
+            // Not little-endian
+            return Err(Error::InvalidFormat)
+        }
+        if ident[4] != C::CLASS {
+            return Err(Error::WrongClass { expected: C::CLASS, actual: ident[4] })
+        }
+        if ident[6] != 1 || elf.version() != 1 {
+            return Err(Error::InvalidVersion)
+        }
+        elf.machine().validate()?;
+        Ok(elf)
+    }
+    /// Decode `flags` as the RISC-V processor-specific flags.
+    fn riscv_flags(&self) -> RiscVFlags {
+        RiscVFlags(self.flags())
+    }
+    fn program_header<'a>(&self, elf: &'a [u8], index: u32) -> Result<&'a C::ProgramHeader> {
+        if index >= self.program_count(elf)? {
+            Err(Error::IndexOutOfRange)
+        } else {
+            let offset = crate::table_offset(self.ph_offset().0.into(), index, self.ph_entry_size())?;
+            C::ProgramHeader::view(elf.get(offset..).ok_or(Error::UnexpectedEoF)?)
+        }
+    }
+    fn section_header<'a>(&self, elf: &'a [u8], index: u32) -> Result<&'a C::SectionHeader> {
+        if index >= self.section_count(elf)? {
+            Err(Error::IndexOutOfRange)
+        } else {
+            let offset = crate::table_offset(self.sh_offset().0.into(), index, self.sh_entry_size())?;
+            C::SectionHeader::view(elf.get(offset..).ok_or(Error::UnexpectedEoF)?)
+        }
+    }
+    /// The number of program headers.
+    ///
+    /// If there are too many to fit in `ph_count` it holds `PN_XNUM` (0xFFFF) and the real count is held in the `info`
+    /// field of section header 0.
+    fn program_count(&self, elf: &[u8]) -> Result<u32> {
+        if self.ph_count() != 0xFFFF {
+            return Ok(self.ph_count().into())
+        }
+        first_section_header::<C, Self>(self, elf)?.map(|header| header.info()).ok_or(Error::IndexOutOfRange)
+    }
+    /// The number of section headers.
+    ///
+    /// If there are `SHN_LORESERVE` (0xFF00) or more, `sh_count` is 0 and the real count is held in the `size` field of
+    /// section header 0.
+    fn section_count(&self, elf: &[u8]) -> Result<u32> {
+        if self.sh_count() != 0 {
+            return Ok(self.sh_count().into())
+        }
+        first_section_header::<C, Self>(self, elf)?.map_or(Ok(0), |header| header.size().as_u32())
+    }
+    /// The index of the section header string table.
+    ///
+    /// If it does not fit below `SHN_LORESERVE`, `section_name_table` holds `SHN_XINDEX` (0xFFFF) and the real index is
+    /// held in the `link` field of section header 0.
+    fn section_name_index(&self, elf: &[u8]) -> Result<u32> {
+        if self.section_name_table() != 0xFFFF {
+            return Ok(self.section_name_table().into())
+        }
+        first_section_header::<C, Self>(self, elf)?.map(|header| header.link()).ok_or(Error::IndexOutOfRange)
+    }
+}
+/// Section header 0, which holds the extended counts, or `None` if the file has no section headers.
+fn first_section_header<'a, C: Class, H: ElfHeader<C>>(
+    header: &H,
+    elf: &'a [u8]
+) -> Result<Option<&'a C::SectionHeader>> {
+    let offset = header.sh_offset();
+    if offset.0 == C::Word::default() {
+        return Ok(None)
+    }
+    C::SectionHeader::view(elf.get(offset.as_usize()?..).ok_or(Error::UnexpectedEoF)?).map(Some)
+}
+
+/// The fields of `ProgramHeader` and `ProgramHeader64`.
+pub trait ElfProgramHeader<C: Class>: View + fmt::Debug {
+    fn ty(&self) -> ProgramType;
+    fn flags(&self) -> ProgramFlags;
+    fn offset(&self) -> Offset<C::Word>;
+    fn virt_addr(&self) -> Address<C::Word>;
+    fn phys_addr(&self) -> Address<C::Word>;
+    fn file_size(&self) -> C::Word;
+    fn mem_size(&self) -> C::Word;
+    fn align(&self) -> C::Word;
+
+    /// The bytes of the segment held in the file.
+    fn data<'a>(&self, elf: &'a [u8]) -> Result<&'a [u8]> {
+        let size = self.file_size().as_usize()?;
+        let offset = self.offset().as_usize()?;
+        let end = offset.checked_add(size).ok_or(Error::IntegerOverflow)?;
+        elf.get(offset..end).ok_or(Error::UnexpectedEoF)
+    }
+}
+
+/// The fields of `SectionHeader` and `SectionHeader64`.
+pub trait ElfSectionHeader<C: Class>: View + fmt::Debug {
+    fn name(&self) -> u32;
+    fn ty(&self) -> SectionType;
+    /// The section flags. ELF64 widens the field, but defines no flags in the upper 32 bits, so setting any of them
+    /// is an `Error::IntegerOverflow`.
+    fn flags(&self) -> Result<SectionFlags>;
+    fn address(&self) -> Address<C::Word>;
+    fn offset(&self) -> Offset<C::Word>;
+    fn size(&self) -> C::Word;
+    fn link(&self) -> u32;
+    fn info(&self) -> u32;
+    fn alignment(&self) -> C::Word;
+    fn entry_size(&self) -> C::Word;
+
+    /// The bytes of the section held in the file.
+    fn data<'a>(&self, elf: &'a [u8]) -> Result<&'a [u8]> {
+        // `SHT_NOBITS` sections such as `.bss` occupy no space in the file
+        if self.ty() == SectionType::NoBits {
+            return Ok(&[])
+        }
+        let size = self.size().as_usize()?;
+        let offset = self.offset().as_usize()?;
+        let end = offset.checked_add(size).ok_or(Error::IntegerOverflow)?;
+        elf.get(offset..end).ok_or(Error::UnexpectedEoF)
+    }
+}
+
+/// The fields of `Symbol` and `Symbol64`, along with the values packed into them.
+pub trait ElfSymbol<C: Class>: View + fmt::Debug {
+    fn name(&self) -> u32;
+    fn value(&self) -> Address<C::Word>;
+    fn size(&self) -> C::Word;
+    fn info(&self) -> u8;
+    fn other(&self) -> u8;
+    fn section_index(&self) -> u16;
+
+    /// The binding, stored in the upper 4 bits of `info`.
+    fn binding(&self) -> SymbolBinding {
+        SymbolBinding(self.info() >> 4)
+    }
+    /// The type, stored in the lower 4 bits of `info`.
+    fn ty(&self) -> SymbolType {
+        SymbolType(self.info() & 0xF)
+    }
+    /// The visibility, stored in the lower 2 bits of `other`.
+    fn visibility(&self) -> SymbolVisibility {
+        SymbolVisibility(self.other() & 0b11)
+    }
+    /// The section the symbol is defined relative to.
+    ///
+    /// Use `SymbolTable::section` to resolve `SymbolSection::Extended`.
+    fn section(&self) -> SymbolSection {
+        SymbolSection::from(self.section_index())
+    }
+    /// Returns true if the symbol is defined in another object.
+    fn is_undefined(&self) -> bool {
+        self.section_index() == 0
+    }
+}
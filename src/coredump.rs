@@ -0,0 +1,576 @@
+use core::{fmt, mem::size_of};
+use crate::{
+    Address, Elf, Error, FileType, Header, Le, Machine, Note, NoteHeader, NoteIter, Offset, Program, ProgramFlags,
+    ProgramHeader, ProgramType, Result, Sink, TableIter, View
+};
+
+/// A view of a core dump (`ET_CORE`) written by the RV32 Linux kernel for a crashed user process.
+///
+/// The process state is held in the notes of the `PT_NOTE` segment and its memory in the `PT_LOAD` segments. Segments
+/// the kernel chose not to dump, such as unmodified file-backed text, have a file size of 0.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// let core = CoreFile::new(Elf::from_bytes(include_bytes!("../examples/core.elf"))?)?;
+/// let info = core.process_info()?.unwrap();
+/// assert_eq!((info.pid.get(), info.name(), info.args()), (42, &b"crash"[..], &b"./crash --now"[..]));
+///
+/// let mut threads = core.threads();
+/// let thread = threads.next().unwrap()?;
+/// assert_eq!((thread.status.pid.get(), thread.status.signal.get()), (42, 11));
+/// assert_eq!(thread.status.pc(), 0x10074);
+/// assert_eq!(thread.status.register(2)?, 0x200);
+/// assert_eq!(thread.fp.unwrap().f[0].get(), 1f64.to_bits());
+/// assert_eq!(threads.next().unwrap()?.status.pid.get(), 43);
+/// assert!(threads.next().is_none());
+///
+/// let file = core.files()?.unwrap().next().unwrap()?;
+/// assert_eq!((file.start, file.end, file.offset, file.name), (0x10000, 0x11000, 0, &b"/bin/crash"[..]));
+///
+/// // Reads may cross into the next segment, but not into memory that was not dumped
+/// let mut buffer = [0; 4];
+/// core.read(0x11FFE, &mut buffer)?;
+/// assert_eq!(buffer, [0x11, 0x11, 0x12, 0x12]);
+/// assert!(matches!(core.read(0x10000, &mut buffer), Err(Error::AddressOutOfRange)));
+/// # Ok(()) })().unwrap()
+/// ```
+#[derive(Clone, Copy)]
+pub struct CoreFile<'a> {
+    pub elf: Elf<'a>,
+    notes: &'a [u8]
+}
+impl<'a> CoreFile<'a> {
+    /// Owner of the notes describing the process.
+    pub const CORE: &'static [u8] = b"CORE";
+
+    /// Coerce a file into a core dump, which must be of type `ET_CORE`.
+    pub fn new(elf: Elf<'a>) -> Result<Self> {
+        elf.check_type(FileType::Core)?;
+        let mut notes: &[u8] = &[];
+        for program in elf.programs()? {
+            let program = program?;
+            if program.header.ty == ProgramType::Note {
+                notes = program.data;
+                break
+            }
+        }
+        Ok(Self { elf, notes })
+    }
+    /// Get an iterator over the notes describing the process.
+    pub fn notes(&self) -> NoteIter<'a> {
+        NoteIter::new(self.notes, 4)
+    }
+    /// Find the first note of type `ty` owned by `"CORE"`.
+    pub fn find_note(&self, ty: CoreNoteType) -> Result<Option<Note<'a>>> {
+        self.notes().find_note(Self::CORE, ty.into())
+    }
+    /// Get an iterator over the `PT_LOAD` segments holding the process memory.
+    pub fn segments(&self) -> Result<CoreSegments<'a>> {
+        Ok(CoreSegments { programs: self.elf.programs()? })
+    }
+    /// Get an iterator over the threads, each described by an `NT_PRSTATUS` note followed by its other register notes.
+    ///
+    /// The thread that caused the dump comes first.
+    pub fn threads(&self) -> Threads<'a> {
+        Threads { notes: self.notes() }
+    }
+    /// Decode the `NT_PRPSINFO` note describing the process.
+    pub fn process_info(&self) -> Result<Option<&'a ProcessInfo>> {
+        self.find_note(CoreNoteType::PrPsInfo)?.map(|note| ProcessInfo::view(note.desc)).transpose()
+    }
+    /// Decode the `NT_FILE` note listing the memory mapped files.
+    pub fn files(&self) -> Result<Option<MappedFiles<'a>>> {
+        self.find_note(CoreNoteType::File)?.map(|note| MappedFiles::new(note.desc)).transpose()
+    }
+    /// Fill `buffer` with the process memory at the virtual `address`, which may span several segments.
+    ///
+    /// Returns `Error::AddressOutOfRange` if any of the range was not dumped.
+    pub fn read(&self, address: u32, buffer: &mut [u8]) -> Result<()> {
+        let mut done = 0;
+        'outer: while done < buffer.len() {
+            let address = u32::try_from(done).ok()
+                .and_then(|done| address.checked_add(done))
+                .ok_or(Error::IntegerOverflow)?;
+            for segment in self.segments()? {
+                let segment = segment?;
+                let start = segment.header.virt_addr.get().0;
+                let Some(offset) = address.checked_sub(start) else { continue };
+                // Only the part held in the file was dumped
+                let Some(data) = segment.data.get(offset as usize..) else { continue };
+                if data.is_empty() {
+                    continue
+                }
+                let size = data.len().min(buffer.len() - done);
+                buffer[done..done + size].copy_from_slice(&data[..size]);
+                done += size;
+                continue 'outer
+            }
+            return Err(Error::AddressOutOfRange)
+        }
+        Ok(())
+    }
+}
+impl<'a> fmt::Debug for CoreFile<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CoreFile")
+            .field("elf", &self.elf)
+            .field("notes", &[..])
+            .finish()
+    }
+}
+
+/// An iterator over the `PT_LOAD` segments of a core dump.
+pub struct CoreSegments<'a> {
+    programs: TableIter<'a, Program<'a>>
+}
+impl<'a> Iterator for CoreSegments<'a> {
+    type Item = Result<Program<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.programs.find(|program| !matches!(program, Ok(program) if program.header.ty != ProgramType::Load))
+    }
+}
+
+/// The register state of one thread.
+#[derive(Debug, Clone, Copy)]
+pub struct Thread<'a> {
+    pub status: &'a ThreadStatus,
+    /// The floating-point registers from `NT_FPREGSET`, or `None` if the process did not use them.
+    pub fp: Option<&'a FpRegisters>
+}
+
+/// An iterator over the threads in a core dump.
+#[derive(Debug, Clone)]
+pub struct Threads<'a> {
+    notes: NoteIter<'a>
+}
+impl<'a> Iterator for Threads<'a> {
+    type Item = Result<Thread<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let is_core = |note: &Note, ty: CoreNoteType| note.name == CoreFile::CORE && note.ty == ty.into();
+        let status = loop {
+            match self.notes.next()? {
+                Ok(note) if is_core(&note, CoreNoteType::PrStatus) => break note,
+                Ok(_) => continue,
+                Err(error) => return Some(Err(error))
+            }
+        };
+        let result = (|| {
+            let mut thread = Thread { status: ThreadStatus::view(status.desc)?, fp: None };
+            // The notes for a thread follow its `NT_PRSTATUS` until the next thread starts
+            let mut rest = self.notes.clone();
+            while let Some(note) = rest.next() {
+                let note = note?;
+                if is_core(&note, CoreNoteType::PrStatus) {
+                    break
+                }
+                if is_core(&note, CoreNoteType::FpRegSet) {
+                    thread.fp = Some(FpRegisters::view(note.desc)?);
+                }
+                self.notes = rest.clone();
+            }
+            Ok(thread)
+        })();
+        Some(result)
+    }
+}
+
+view!{
+    /// The `NT_PRSTATUS` descriptor, `struct elf_prstatus` for RV32.
+    #[derive(Debug)]
+    pub struct ThreadStatus {
+        /// The signal number, from `pr_info.si_signo`.
+        pub signal: Le<i32>,
+        pub code: Le<i32>,
+        pub errno: Le<i32>,
+        pub current_signal: Le<u16>,
+        _padding: [u8; 2],
+        pub pending_signals: Le<u32>,
+        pub held_signals: Le<u32>,
+        pub pid: Le<i32>,
+        pub ppid: Le<i32>,
+        pub pgrp: Le<i32>,
+        pub sid: Le<i32>,
+        /// Each time is a `timeval` of seconds and microseconds.
+        pub user_time: [Le<u32>; 2],
+        pub system_time: [Le<u32>; 2],
+        pub children_user_time: [Le<u32>; 2],
+        pub children_system_time: [Le<u32>; 2],
+        /// `pc` followed by `x1` to `x31`, as in `struct user_regs_struct`.
+        pub registers: [Le<u32>; 32],
+        pub fp_valid: Le<i32>
+    }
+}
+impl ThreadStatus {
+    /// Create the status of a thread stopped by `signal`, with `registers` holding `pc` followed by `x1` to `x31`.
+    ///
+    /// The remaining fields are zero, other than the process group and session which are set to `pid`.
+    pub fn new(pid: i32, signal: i32, registers: [u32; 32]) -> Self {
+        let time = [Le::new(0); 2];
+        Self {
+            signal: signal.into(),
+            code: 0.into(),
+            errno: 0.into(),
+            current_signal: (signal as u16).into(),
+            _padding: [0; 2],
+            pending_signals: 0.into(),
+            held_signals: 0.into(),
+            pid: pid.into(),
+            ppid: 0.into(),
+            pgrp: pid.into(),
+            sid: pid.into(),
+            user_time: time,
+            system_time: time,
+            children_user_time: time,
+            children_system_time: time,
+            registers: registers.map(Le::new),
+            fp_valid: 0.into()
+        }
+    }
+    /// The program counter.
+    pub fn pc(&self) -> u32 {
+        self.registers[0].get()
+    }
+    /// The integer register `x{index}`, where `x0` always reads as 0.
+    pub fn register(&self, index: u32) -> Result<u32> {
+        match index {
+            0 => Ok(0),
+            1..=31 => Ok(self.registers[index as usize].get()),
+            _ => Err(Error::IndexOutOfRange)
+        }
+    }
+}
+
+view!{
+    /// The `NT_FPREGSET` descriptor, `struct __riscv_d_ext_state`.
+    ///
+    /// The registers are 64 bits wide, with single-precision values NaN-boxed in the upper half.
+    #[derive(Debug)]
+    pub struct FpRegisters {
+        pub f: [Le<u64>; 32],
+        pub fcsr: Le<u32>
+    }
+}
+
+view!{
+    /// The `NT_PRPSINFO` descriptor, `struct elf_prpsinfo` for RV32.
+    #[derive(Debug)]
+    pub struct ProcessInfo {
+        /// The numeric process state, with `state_name` holding its letter such as `R` or `S`.
+        pub state: u8,
+        pub state_name: u8,
+        pub zombie: u8,
+        /// The nice value, a signed byte.
+        pub nice: u8,
+        pub flags: Le<u32>,
+        pub uid: Le<u32>,
+        pub gid: Le<u32>,
+        pub pid: Le<i32>,
+        pub ppid: Le<i32>,
+        pub pgrp: Le<i32>,
+        pub sid: Le<i32>,
+        pub file_name: [u8; 16],
+        pub args: [u8; 80]
+    }
+}
+impl ProcessInfo {
+    /// Create the description of a running process, truncating `name` to 15 bytes and `args` to 79.
+    pub fn new(pid: i32, name: &[u8], args: &[u8]) -> Self {
+        let mut file_name = [0; 16];
+        let mut all_args = [0; 80];
+        let name = &name[..name.len().min(file_name.len() - 1)];
+        let args = &args[..args.len().min(all_args.len() - 1)];
+        file_name[..name.len()].copy_from_slice(name);
+        all_args[..args.len()].copy_from_slice(args);
+        Self {
+            state: 0,
+            state_name: b'R',
+            zombie: 0,
+            nice: 0,
+            flags: 0.into(),
+            uid: 0.into(),
+            gid: 0.into(),
+            pid: pid.into(),
+            ppid: 0.into(),
+            pgrp: pid.into(),
+            sid: pid.into(),
+            file_name,
+            args: all_args
+        }
+    }
+    /// The name of the executable, truncated to 15 bytes.
+    pub fn name(&self) -> &[u8] {
+        until_null(&self.file_name)
+    }
+    /// The start of the command line, with arguments separated by spaces.
+    pub fn args(&self) -> &[u8] {
+        until_null(&self.args)
+    }
+}
+
+fn until_null(bytes: &[u8]) -> &[u8] {
+    memchr::memchr(0, bytes).map_or(bytes, |end| &bytes[..end])
+}
+
+/// An iterator over the entries of an `NT_FILE` note, each a file mapped into the process.
+#[derive(Clone)]
+pub struct MappedFiles<'a> {
+    entries: &'a [u8],
+    names: &'a [u8],
+    page_size: u32
+}
+impl<'a> MappedFiles<'a> {
+    /// Parse the descriptor of an `NT_FILE` note.
+    pub fn new(desc: &'a [u8]) -> Result<Self> {
+        let header = <[Le<u32>; 2]>::view(desc)?;
+        let size = (header[0].get() as usize).checked_mul(12).ok_or(Error::IntegerOverflow)?;
+        let entries = desc.get(8..).and_then(|entries| entries.get(..size)).ok_or(Error::UnexpectedEoF)?;
+        Ok(Self {
+            entries,
+            names: &desc[8 + size..],
+            page_size: header[1].get()
+        })
+    }
+    /// The page size used for the file offsets.
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+}
+impl<'a> Iterator for MappedFiles<'a> {
+    type Item = Result<MappedFile<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = <[Le<u32>; 3]>::view(self.entries).ok()?;
+        self.entries = &self.entries[12..];
+        let result = (|| {
+            let end = memchr::memchr(0, self.names).ok_or(Error::UnterminatedString)?;
+            let name = &self.names[..end];
+            self.names = &self.names[end + 1..];
+            Ok(MappedFile {
+                start: entry[0].get(),
+                end: entry[1].get(),
+                offset: u64::from(entry[2].get()) * u64::from(self.page_size),
+                name
+            })
+        })();
+        if result.is_err() {
+            self.entries = &[];
+        }
+        Some(result)
+    }
+}
+impl<'a> fmt::Debug for MappedFiles<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MappedFiles")
+            .field("entries", &[..])
+            .field("names", &[..])
+            .field("page_size", &self.page_size)
+            .finish()
+    }
+}
+
+/// A file mapped into the process.
+#[derive(Debug, Clone, Copy)]
+pub struct MappedFile<'a> {
+    /// The virtual address range of the mapping.
+    pub start: u32,
+    pub end: u32,
+    /// The offset into the file in bytes.
+    pub offset: u64,
+    pub name: &'a [u8]
+}
+
+/// A region of process memory to include in a core dump.
+#[derive(Debug, Clone, Copy)]
+pub struct CoreRegion<'a> {
+    pub address: u32,
+    pub data: &'a [u8],
+    pub flags: ProgramFlags
+}
+impl<'a> CoreRegion<'a> {
+    pub fn new(address: u32, data: &'a [u8], flags: ProgramFlags) -> Self {
+        Self {
+            address,
+            data,
+            flags
+        }
+    }
+}
+
+/// Writes a core dump in the layout GDB expects from the RV32 Linux kernel, without allocating.
+///
+/// The file holds the ELF header, a `PT_NOTE` segment with the `NT_PRSTATUS` of each thread and the `NT_PRPSINFO` of
+/// the process, then a `PT_LOAD` segment for each region.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// let mut registers = [0; 32];
+/// registers[0] = 0x10074;
+/// registers[2] = 0x7FFF_FF00;
+/// let threads = [ThreadStatus::new(42, 11, registers)];
+/// let info = ProcessInfo::new(42, b"crash", b"./crash --now");
+/// let stack = [0xAA; 0x100];
+/// let regions = [CoreRegion::new(0x7FFF_FF00, &stack, ProgramFlags::Read | ProgramFlags::Write)];
+///
+/// let writer = CoreWriter::new(&threads, &info, &regions);
+/// let mut buffer = [0; 1024];
+/// let mut sink = SliceSink::new(&mut buffer);
+/// writer.write(&mut sink)?;
+/// assert_eq!(sink.len(), writer.size()?);
+///
+/// let core = CoreFile::new(Elf::from_bytes(sink.written())?)?;
+/// let thread = core.threads().next().unwrap()?;
+/// assert_eq!((thread.status.pid.get(), thread.status.pc(), thread.status.register(2)?), (42, 0x10074, 0x7FFF_FF00));
+/// assert_eq!(core.process_info()?.unwrap().name(), b"crash");
+/// let mut word = [0; 4];
+/// core.read(0x7FFF_FFFC, &mut word)?;
+/// assert_eq!(word, [0xAA; 4]);
+/// # Ok(()) })().unwrap()
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct CoreWriter<'a> {
+    /// The threads of the process, starting with the one that caused the dump.
+    pub threads: &'a [ThreadStatus],
+    pub info: &'a ProcessInfo,
+    pub regions: &'a [CoreRegion<'a>]
+}
+impl<'a> CoreWriter<'a> {
+    pub fn new(threads: &'a [ThreadStatus], info: &'a ProcessInfo, regions: &'a [CoreRegion<'a>]) -> Self {
+        Self {
+            threads,
+            info,
+            regions
+        }
+    }
+    /// Returns the size of the file in bytes.
+    pub fn size(&self) -> Result<usize> {
+        self.layout(|_, _| Ok(()))
+    }
+    /// Write the file to `sink`, returning its size in bytes.
+    ///
+    /// Fails with `Error::IntegerOverflow` if there are more than 65533 regions, as together with the `PT_NOTE` segment
+    /// they would reach `PN_XNUM` (0xFFFF), which marks a count held elsewhere.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// let info = ProcessInfo::new(1, b"init", b"init");
+    /// let regions = [CoreRegion::new(0, &[], ProgramFlags::Read); 0xFFFE];
+    /// let mut buffer = [0; 64];
+    /// let result = CoreWriter::new(&[], &info, &regions).write(&mut SliceSink::new(&mut buffer));
+    /// assert!(matches!(result, Err(Error::IntegerOverflow)));
+    /// // One fewer fits, leaving only the buffer too small
+    /// let result = CoreWriter::new(&[], &info, &regions[1..]).write(&mut SliceSink::new(&mut buffer));
+    /// assert!(!matches!(result, Err(Error::IntegerOverflow)));
+    /// ```
+    pub fn write(&self, sink: &mut impl Sink) -> Result<usize> {
+        let notes = self.notes_offset();
+        let header = Header {
+            ident: *b"\x7fELF\x01\x01\x01\0\0\0\0\0\0\0\0\0",
+            ty: FileType::Core.into(),
+            machine: Machine::RiscV.into(),
+            version: 1.into(),
+            entry: Address(0).into(),
+            ph_offset: Offset(size_of::<Header>() as u32).into(),
+            sh_offset: Offset(0).into(),
+            flags: 0.into(),
+            header_size: (size_of::<Header>() as u16).into(),
+            ph_entry_size: (size_of::<ProgramHeader>() as u16).into(),
+            ph_count: u16::try_from(self.regions.len() + 1)
+                .ok()
+                .filter(|&count| count < 0xFFFF)
+                .ok_or(Error::IntegerOverflow)?
+                .into(),
+            sh_entry_size: 0.into(),
+            sh_count: 0.into(),
+            section_name_table: 0.into()
+        };
+        sink.write(header.as_bytes())?;
+        sink.write(program_header(ProgramType::Note, notes, 0, self.notes_size(), ProgramFlags(0), 4)?.as_bytes())?;
+        self.layout(|region, offset| {
+            let size = region.data.len();
+            sink.write(program_header(ProgramType::Load, offset, region.address, size, region.flags, 4)?.as_bytes())
+        })?;
+
+        for (index, status) in self.threads.iter().enumerate() {
+            write_note(sink, CoreNoteType::PrStatus, status.as_bytes())?;
+            if index == 0 {
+                write_note(sink, CoreNoteType::PrPsInfo, self.info.as_bytes())?;
+            }
+        }
+        if self.threads.is_empty() {
+            write_note(sink, CoreNoteType::PrPsInfo, self.info.as_bytes())?;
+        }
+        let mut position = notes + self.notes_size();
+        self.layout(|region, offset| {
+            sink.zeros(offset - position)?;
+            sink.write(region.data)?;
+            position = offset + region.data.len();
+            Ok(())
+        })
+    }
+    fn notes_offset(&self) -> usize {
+        size_of::<Header>() + (self.regions.len() + 1) * size_of::<ProgramHeader>()
+    }
+    fn notes_size(&self) -> usize {
+        let note = size_of::<NoteHeader>() + 8;
+        self.threads.len() * (note + size_of::<ThreadStatus>()) + note + size_of::<ProcessInfo>()
+    }
+    /// Call `place` with the file offset of each region, returning the end of the file.
+    ///
+    /// Each region is placed at an offset congruent to its address modulo the segment alignment of 4.
+    fn layout(&self, mut place: impl FnMut(&CoreRegion<'a>, usize) -> Result<()>) -> Result<usize> {
+        let mut offset = self.notes_offset() + self.notes_size();
+        for region in self.regions {
+            offset += (region.address as usize).wrapping_sub(offset) & 3;
+            place(region, offset)?;
+            offset = offset.checked_add(region.data.len()).ok_or(Error::IntegerOverflow)?;
+        }
+        Ok(offset)
+    }
+}
+
+fn program_header(
+    ty: ProgramType,
+    offset: usize,
+    address: u32,
+    size: usize,
+    flags: ProgramFlags,
+    align: u32
+) -> Result<ProgramHeader> {
+    let offset = u32::try_from(offset).map_err(|_| Error::IntegerOverflow)?;
+    let size = u32::try_from(size).map_err(|_| Error::IntegerOverflow)?;
+    Ok(ProgramHeader {
+        ty: ty.into(),
+        offset: Offset(offset).into(),
+        virt_addr: Address(address).into(),
+        phys_addr: Address(0).into(),
+        file_size: size.into(),
+        mem_size: size.into(),
+        flags: flags.into(),
+        align: align.into()
+    })
+}
+
+/// Write a note owned by `"CORE"`, whose descriptor is a multiple of 4 bytes.
+fn write_note(sink: &mut impl Sink, ty: CoreNoteType, desc: &[u8]) -> Result<()> {
+    let header = NoteHeader {
+        name_size: 5.into(),
+        desc_size: (desc.len() as u32).into(),
+        ty: ty.0.into()
+    };
+    sink.write(header.as_bytes())?;
+    sink.write(b"CORE\0\0\0\0")?;
+    sink.write(desc)
+}
+
+c_enum!{
+    pub CoreNoteType(u32) {
+        PrStatus = 1,
+        FpRegSet = 2,
+        PrPsInfo = 3,
+        TaskStruct = 4,
+        Auxv = 6,
+        SigInfo = 0x53494749,
+        File = 0x46494C45
+    } v => Err(Error::UnsupportedCoreNoteType(Self(v)))
+}
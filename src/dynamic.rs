@@ -0,0 +1,569 @@
+use core::{mem::size_of, fmt, ops::Range};
+use crate::{
+    bytes::read_u32, Elf, Elf32, ElfSymbol, Error, GnuHashTable, HashTable, Le, Memory, Offset, Program, ProgramType,
+    Rel, Rela, Relocation, RelocationType, Result, Section, SectionType, StringTable, Symbol, SymbolBinding,
+    SymbolSection, SymbolTable, TableIter, View
+};
+
+impl<'a> Elf<'a> {
+    /// Get the dynamic section, if the file has one.
+    ///
+    /// The `PT_DYNAMIC` segment is preferred so that files without section headers are still supported.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// # let mut data = [0u32; 8192];
+    /// # let elf = include_bytes!("../examples/libdynamic.so");
+    /// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+    /// let elf = Elf::new(&data)?;
+    /// let dynamic = elf.dynamic()?.unwrap();
+    /// assert_eq!(dynamic.soname()?, Some("libdynamic.so"));
+    /// assert!(dynamic.flags()?.all(DynamicFlags::BindNow));
+    /// assert_eq!(dynamic.init_array()?, Some(0x2290..0x2294));
+    /// let symbols = dynamic.symbols()?.unwrap();
+    /// assert_eq!(symbols.find("version")?.unwrap().1.value.get().as_usize()?, 0x3340);
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn dynamic(&self) -> Result<Option<Dynamic<'a>>> {
+        for program in self.programs()? {
+            let program = program?;
+            if program.header.ty == ProgramType::Dynamic {
+                return Dynamic::from_program(self, program).map(Some)
+            }
+        }
+        self.find_section(SectionType::Dynamic)?
+            .map(|section| Dynamic::from_section(self, section))
+            .transpose()
+    }
+}
+
+/// A view of the `Elf32_Dyn` entries of a `SHT_DYNAMIC` section or `PT_DYNAMIC` segment.
+///
+/// Addresses held by entries such as `DT_STRTAB` are resolved to file data through the `PT_LOAD` segments.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// # let mut data = [0u32; 8192];
+/// # let elf = include_bytes!("../examples/pie.elf");
+/// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+/// let elf = Elf::new(&data)?;
+/// let dynamic = elf.dynamic()?.unwrap();
+/// for needed in dynamic.needed()? {
+///     assert_eq!(needed?, "libdynamic.so");
+/// }
+/// let symbols = dynamic.symbols()?.unwrap();
+/// assert_eq!(symbols.len(), 3);
+/// assert!(symbols.find("add")?.unwrap().1.is_undefined());
+/// assert_eq!(dynamic.rela()?.count(), 3);
+/// assert_eq!(dynamic.plt_rela()?.count(), 1);
+/// # Ok(()) })().unwrap()
+/// ```
+#[derive(Clone, Copy)]
+pub struct Dynamic<'a> {
+    elf: Elf<'a>,
+    data: &'a [u8],
+    count: u32
+}
+impl<'a> Dynamic<'a> {
+    /// Coerce a `SHT_DYNAMIC` section into a dynamic table.
+    pub fn from_section(elf: &Elf<'a>, section: Section<'a>) -> Result<Self> {
+        section.check_type(SectionType::Dynamic)?;
+        Self::new(elf, section.data)
+    }
+    /// Coerce a `PT_DYNAMIC` segment into a dynamic table.
+    pub fn from_program(elf: &Elf<'a>, program: Program<'a>) -> Result<Self> {
+        program.check_type(ProgramType::Dynamic)?;
+        Self::new(elf, program.data)
+    }
+    /// Create a dynamic table from raw entries, which end at the first `DT_NULL`.
+    pub fn new(elf: &Elf<'a>, data: &'a [u8]) -> Result<Self> {
+        let mut dynamic = Self {
+            elf: *elf,
+            data,
+            count: (data.len() / size_of::<Dyn>()) as u32
+        };
+        let count = dynamic.entries()?.position(|entry| entry.is_ok_and(|entry| entry.tag == DynamicTag::Null));
+        if let Some(count) = count {
+            dynamic.count = count as u32;
+        }
+        Ok(dynamic)
+    }
+    /// Get an iterator over the entries before `DT_NULL`.
+    pub fn entries(&self) -> Result<TableIter<'a, &'a Dyn>> {
+        TableIter::new(self.data, Offset(0u32), self.count, size_of::<Dyn>() as u16)
+    }
+    /// Get the value of the first entry with the given tag.
+    pub fn get(&self, tag: DynamicTag) -> Result<Option<u32>> {
+        for entry in self.entries()? {
+            let entry = entry?;
+            if entry.tag == tag {
+                return Ok(Some(entry.value.get()))
+            }
+        }
+        Ok(None)
+    }
+    /// Get the file data for the table at the address given by `address` with a size given by `size`.
+    fn table(&self, address: DynamicTag, size: DynamicTag) -> Result<Option<&'a [u8]>> {
+        match (self.get(address)?, self.get(size)?) {
+            (Some(address), Some(size)) => self.elf.segment_data(address, size).map(Some),
+            (None, _) => Ok(None),
+            (Some(_), None) => Err(Error::MissingDynamicTag(size))
+        }
+    }
+    /// The dynamic string table given by `DT_STRTAB` and `DT_STRSZ`.
+    pub fn strings(&self) -> Result<Option<StringTable<'a>>> {
+        Ok(self.table(DynamicTag::StrTab, DynamicTag::StrSize)?.map(StringTable))
+    }
+    /// Look up a string given by the value of an entry such as `DT_NEEDED`.
+    pub fn string(&self, offset: u32) -> Result<&'a str> {
+        self.strings()?.ok_or(Error::MissingDynamicTag(DynamicTag::StrTab))?.get_str(offset)
+    }
+    /// The names of the shared objects given by `DT_NEEDED`, in order.
+    pub fn needed(&self) -> Result<impl Iterator<Item = Result<&'a str>> + 'a> {
+        let dynamic = *self;
+        Ok(self.entries()?.filter_map(move |entry| match entry {
+            Ok(entry) if entry.tag.get() == DynamicTag::Needed => Some(dynamic.string(entry.value.get())),
+            Ok(_) => None,
+            Err(e) => Some(Err(e))
+        }))
+    }
+    /// The name of this shared object given by `DT_SONAME`.
+    pub fn soname(&self) -> Result<Option<&'a str>> {
+        self.get(DynamicTag::SoName)?.map(|offset| self.string(offset)).transpose()
+    }
+    /// The flags given by `DT_FLAGS`, or no flags if it is absent.
+    pub fn flags(&self) -> Result<DynamicFlags> {
+        Ok(DynamicFlags(self.get(DynamicTag::Flags)?.unwrap_or(0)))
+    }
+    /// The dynamic symbol table given by `DT_SYMTAB`, `DT_SYMENT` and `DT_STRTAB`.
+    ///
+    /// The number of symbols is not recorded in the dynamic section so is found through `DT_HASH` or `DT_GNU_HASH`.
+    pub fn symbols(&self) -> Result<Option<SymbolTable<'a>>> {
+        let address = match self.get(DynamicTag::SymTab)? {
+            Some(address) => address,
+            None => return Ok(None)
+        };
+        let entry_size = self.get(DynamicTag::SymEntry)?.unwrap_or(size_of::<Symbol>() as u32);
+        let count = self.symbol_count()?;
+        let size = count.checked_mul(entry_size).ok_or(Error::IntegerOverflow)?;
+        let strings = self.strings()?.ok_or(Error::MissingDynamicTag(DynamicTag::StrTab))?;
+        SymbolTable::from_data(self.elf.segment_data(address, size)?, entry_size, strings).map(Some)
+    }
+    /// Count the dynamic symbols using the hash tables.
+    fn symbol_count(&self) -> Result<u32> {
+        if let Some(table) = self.hash_table()? {
+            return Ok(table.symbol_count())
+        }
+        if let Some(table) = self.gnu_hash_table()? {
+            return table.symbol_count()
+        }
+        Err(Error::MissingDynamicTag(DynamicTag::Hash))
+    }
+    /// The SysV hash table given by `DT_HASH`.
+    pub fn hash_table(&self) -> Result<Option<HashTable<'a>>> {
+        self.get(DynamicTag::Hash)?.map(|address| HashTable::new(self.data_from(address)?)).transpose()
+    }
+    /// The GNU hash table given by `DT_GNU_HASH`.
+    pub fn gnu_hash_table(&self) -> Result<Option<GnuHashTable<'a>>> {
+        self.get(DynamicTag::GnuHash)?.map(|address| GnuHashTable::new::<Elf32>(self.data_from(address)?)).transpose()
+    }
+    /// Find a dynamic symbol defined by the file by name through `DT_GNU_HASH` or `DT_HASH`, returning it with its
+    /// index in the symbol table. Like `Elf::lookup_symbol`, but works without section headers.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// let elf = Elf::from_bytes(include_bytes!("../examples/libdynamic.so"))?;
+    /// let dynamic = elf.dynamic()?.unwrap();
+    /// let (index, greet) = dynamic.lookup_symbol("greet")?.unwrap();
+    /// assert_eq!(greet.ty(), SymbolType::Function);
+    /// assert_eq!(elf.lookup_symbol("greet")?.unwrap().0, index);
+    /// // `puts` is imported, so is only found through the SysV table
+    /// assert!(dynamic.lookup_symbol("puts")?.is_none());
+    /// let symbols = dynamic.symbols()?.unwrap();
+    /// assert!(dynamic.hash_table()?.unwrap().find(&symbols, "puts")?.unwrap().1.is_undefined());
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn lookup_symbol(&self, name: &str) -> Result<Option<(u32, &'a Symbol)>> {
+        let Some(symbols) = self.symbols()? else { return Ok(None) };
+        let found = match (self.gnu_hash_table()?, self.hash_table()?) {
+            (Some(table), _) => table.find(&symbols, name)?,
+            (None, Some(table)) => table.find(&symbols, name)?,
+            (None, None) => symbols.find(name)?
+        };
+        Ok(found.filter(|(_, symbol)| !symbol.is_undefined()))
+    }
+    /// Get the file data from `address` to the end of the `PT_LOAD` segment holding it, for tables without a size tag.
+    fn data_from(&self, address: u32) -> Result<&'a [u8]> {
+        for program in self.elf.programs()? {
+            let program = program?;
+            if program.header.ty != ProgramType::Load {
+                continue
+            }
+            let Some(offset) = address.checked_sub(program.header.virt_addr.get().0) else { continue };
+            match program.data.get(offset as usize..) {
+                Some(data) if !data.is_empty() => return Ok(data),
+                _ => continue
+            }
+        }
+        Err(Error::AddressOutOfRange)
+    }
+    /// Get the relocations of type `R` in the table given by the address, size and entry size tags.
+    fn relocations<R: 'a + View>(
+        &self,
+        address: DynamicTag,
+        size: DynamicTag,
+        entry: DynamicTag
+    ) -> Result<TableIter<'a, &'a R>> {
+        let data = self.table(address, size)?.unwrap_or(&[]);
+        let entry_size = self.get(entry)?.unwrap_or(size_of::<R>() as u32);
+        if entry_size < size_of::<R>() as u32 {
+            return Err(Error::InvalidEntrySize)
+        }
+        let count = (data.len() / entry_size as usize) as u32;
+        TableIter::new(data, Offset(0u32), count, entry_size.try_into().map_err(|_| Error::InvalidEntrySize)?)
+    }
+    /// The relocations given by `DT_RELA`, `DT_RELASZ` and `DT_RELAENT`.
+    pub fn rela(&self) -> Result<TableIter<'a, &'a Rela>> {
+        self.relocations(DynamicTag::Rela, DynamicTag::RelaSize, DynamicTag::RelaEntry)
+    }
+    /// The relocations given by `DT_REL`, `DT_RELSZ` and `DT_RELENT`.
+    pub fn rel(&self) -> Result<TableIter<'a, &'a Rel>> {
+        self.relocations(DynamicTag::Rel, DynamicTag::RelSize, DynamicTag::RelEntry)
+    }
+    /// The kind of PLT relocation given by `DT_PLTREL`, either `DynamicTag::Rela` or `DynamicTag::Rel`. Without the
+    /// tag they are taken to be `Rela` entries, which is all RISC-V uses.
+    fn plt_type(&self) -> Result<DynamicTag> {
+        Ok(self.get(DynamicTag::PltRel)?.map_or(DynamicTag::Rela, DynamicTag))
+    }
+    /// The PLT relocations given by `DT_JMPREL` and `DT_PLTRELSZ` when `DT_PLTREL` is `DT_RELA`.
+    ///
+    /// Returns `Error::WrongDynamicTag` if `DT_PLTREL` is another kind, such as `DT_REL` for `plt_rel`.
+    pub fn plt_rela(&self) -> Result<TableIter<'a, &'a Rela>> {
+        self.check_plt_type(DynamicTag::Rela)?;
+        self.relocations(DynamicTag::JmpRel, DynamicTag::PltRelSize, DynamicTag::RelaEntry)
+    }
+    /// The PLT relocations given by `DT_JMPREL` and `DT_PLTRELSZ` when `DT_PLTREL` is `DT_REL`.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// let elf = Elf::from_bytes(include_bytes!("../examples/pie.elf"))?;
+    /// let dynamic = elf.dynamic()?.unwrap();
+    /// // RISC-V only uses `Rela` entries
+    /// let error = dynamic.plt_rel().err();
+    /// assert!(matches!(error, Some(Error::WrongDynamicTag { expected: DynamicTag::Rel, actual: DynamicTag::Rela })));
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn plt_rel(&self) -> Result<TableIter<'a, &'a Rel>> {
+        self.check_plt_type(DynamicTag::Rel)?;
+        self.relocations(DynamicTag::JmpRel, DynamicTag::PltRelSize, DynamicTag::RelEntry)
+    }
+    fn check_plt_type(&self, expected: DynamicTag) -> Result<()> {
+        let actual = self.plt_type()?;
+        if actual != expected {
+            Err(Error::WrongDynamicTag { expected, actual })
+        } else {
+            Ok(())
+        }
+    }
+    /// The addresses of the packed relative relocations given by `DT_RELR`, `DT_RELRSZ` and `DT_RELRENT`.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// let elf = Elf::from_bytes(include_bytes!("../examples/relr.elf"))?;
+    /// let dynamic = elf.dynamic()?.unwrap();
+    /// // `table` holds `_start`, itself, a zero word and 36 more pointers, with `last` 256 bytes later
+    /// let mut relr = dynamic.relr()?;
+    /// assert_eq!(relr.next().transpose()?, Some(0x31EC));
+    /// assert_eq!(relr.next().transpose()?, Some(0x31F0));
+    /// assert_eq!(relr.next().transpose()?, Some(0x31F8));
+    /// assert_eq!(relr.last().transpose()?, Some(0x3388));
+    /// assert_eq!(dynamic.relr()?.count(), 39);
+    ///
+    /// let bias = 0x8000_0000;
+    /// let mut buffer = [0u8; 0x4000];
+    /// let mut memory = Region::new(bias, &mut buffer);
+    /// Loader::with_bias(&elf, bias).load(&mut memory)?;
+    /// dynamic.relocate(&mut memory, bias, |_| None)?;
+    /// assert_eq!(memory.read_u32(0x8000_31EC)?, 0x8000_1180);
+    /// assert_eq!(memory.read_u32(0x8000_31F4)?, 0);
+    /// assert_eq!(memory.read_u32(0x8000_3284)?, 0x8000_31EC + 35 * 4);
+    /// assert_eq!(memory.read_u32(0x8000_3388)?, 0x8000_3388);
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn relr(&self) -> Result<RelrIter<'a>> {
+        if self.get(DynamicTag::RelrEntry)?.is_some_and(|size| size != 4) {
+            return Err(Error::InvalidEntrySize)
+        }
+        Ok(RelrIter {
+            data: self.table(DynamicTag::Relr, DynamicTag::RelrSize)?.unwrap_or(&[]),
+            index: 0,
+            next: None,
+            base: 0,
+            bitmap: 0
+        })
+    }
+    /// Apply the `DT_RELA`, `DT_REL`, `DT_RELR` and `DT_JMPREL` relocations to an image loaded at `bias`.
+    ///
+    /// Only the relocations needed by position-independent executables are supported: `R_RISCV_RELATIVE`,
+    /// `R_RISCV_32` and `R_RISCV_JUMP_SLOT`, along with the packed relative relocations of `DT_RELR`. Symbols
+    /// defined by the image are relocated by `bias`, while undefined symbols are looked up by name through `resolve`.
+    /// Nothing is allocated, so this is suitable for an image relocating itself during early boot.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// # let mut data = [0u32; 8192];
+    /// # let elf = include_bytes!("../examples/pie.elf");
+    /// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+    /// let elf = Elf::new(&data)?;
+    /// let bias = 0x8000_0000;
+    /// let mut buffer = [0u8; 0x4000];
+    /// let mut memory = Region::new(bias, &mut buffer);
+    /// let loaded = Loader::with_bias(&elf, bias).load(&mut memory)?;
+    /// assert_eq!(loaded.entry, 0x8000_11EC);
+    /// elf.dynamic()?.unwrap().relocate(&mut memory, bias, |name| match name {
+    ///     "add" => Some(0x4000_0000),
+    ///     "version" => Some(0x4000_1000),
+    ///     _ => None
+    /// })?;
+    /// // `table` holds `_start`, `message` and `version`
+    /// assert_eq!(memory.read_u32(0x8000_32D0)?, 0x8000_11EC);
+    /// assert_eq!(memory.read_u32(0x8000_32D4)?, 0x8000_01E8);
+    /// assert_eq!(memory.read_u32(0x8000_32D8)?, 0x4000_1000);
+    /// // The PLT slot for `add`
+    /// assert_eq!(memory.read_u32(0x8000_32E4)?, 0x4000_0000);
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn relocate(
+        &self,
+        memory: &mut impl Memory,
+        bias: u32,
+        mut resolve: impl FnMut(&str) -> Option<u32>
+    ) -> Result<()> {
+        // Read on the first symbol relocation, as a file with only `R_RISCV_RELATIVE` need not have a symbol table
+        let mut symbols = None;
+        let (plt_rela, plt_rel) = match self.plt_type()? {
+            DynamicTag::Rel => (None, Some(self.plt_rel()?)),
+            _ => (Some(self.plt_rela()?), None)
+        };
+        for rela in self.rela()?.chain(plt_rela.into_iter().flatten()) {
+            let rela = rela?;
+            let p = rela.offset.get().0.wrapping_add(bias);
+            let value = match rela.ty() {
+                RelocationType::None => continue,
+                RelocationType::Relative => bias.wrapping_add(rela.addend.get() as u32),
+                RelocationType::Abs32 => {
+                    let value = self.symbol_value(&mut symbols, rela.symbol(), bias, &mut resolve)?;
+                    value.wrapping_add(rela.addend.get() as u32)
+                }
+                RelocationType::JumpSlot => self.symbol_value(&mut symbols, rela.symbol(), bias, &mut resolve)?,
+                ty => return Err(Error::UnsupportedRelocationType(ty))
+            };
+            memory.write_u32(p, value)?;
+        }
+        for rel in self.rel()?.chain(plt_rel.into_iter().flatten()) {
+            let rel = rel?;
+            let p = rel.offset.get().0.wrapping_add(bias);
+            // The addend is held in the location being relocated
+            let value = match rel.ty() {
+                RelocationType::None => continue,
+                RelocationType::Relative => memory.read_u32(p)?.wrapping_add(bias),
+                RelocationType::Abs32 => {
+                    let value = self.symbol_value(&mut symbols, rel.symbol(), bias, &mut resolve)?;
+                    memory.read_u32(p)?.wrapping_add(value)
+                }
+                RelocationType::JumpSlot => self.symbol_value(&mut symbols, rel.symbol(), bias, &mut resolve)?,
+                ty => return Err(Error::UnsupportedRelocationType(ty))
+            };
+            memory.write_u32(p, value)?;
+        }
+        for address in self.relr()? {
+            let p = address?.wrapping_add(bias);
+            let value = memory.read_u32(p)?.wrapping_add(bias);
+            memory.write_u32(p, value)?;
+        }
+        Ok(())
+    }
+    /// The run-time value of a dynamic symbol.
+    ///
+    /// `symbols` caches the dynamic symbol table across calls, and is filled in on first use.
+    fn symbol_value(
+        &self,
+        symbols: &mut Option<SymbolTable<'a>>,
+        index: u32,
+        bias: u32,
+        resolve: &mut impl FnMut(&str) -> Option<u32>
+    ) -> Result<u32> {
+        if index == 0 {
+            return Ok(0)
+        }
+        let symbols = match symbols {
+            Some(symbols) => symbols,
+            None => symbols.insert(self.symbols()?.ok_or(Error::MissingDynamicTag(DynamicTag::SymTab))?)
+        };
+        let symbol = symbols.get(index)?;
+        match symbol.section() {
+            SymbolSection::Undefined => match resolve(symbols.name(symbol)?) {
+                Some(value) => Ok(value),
+                None if symbol.binding() == SymbolBinding::Weak => Ok(0),
+                None => Err(Error::UnresolvedSymbol(index))
+            },
+            SymbolSection::Absolute => Ok(symbol.value.get().0),
+            _ => Ok(symbol.value.get().0.wrapping_add(bias))
+        }
+    }
+    /// The address range given by a pair of array tags.
+    fn array(&self, address: DynamicTag, size: DynamicTag) -> Result<Option<Range<u32>>> {
+        match (self.get(address)?, self.get(size)?) {
+            (Some(address), Some(size)) => Ok(Some(address..address.checked_add(size).ok_or(Error::IntegerOverflow)?)),
+            (None, _) => Ok(None),
+            (Some(_), None) => Err(Error::MissingDynamicTag(size))
+        }
+    }
+    /// The address range of the initialisers given by `DT_PREINIT_ARRAY` and `DT_PREINIT_ARRAYSZ`.
+    pub fn preinit_array(&self) -> Result<Option<Range<u32>>> {
+        self.array(DynamicTag::PreinitArray, DynamicTag::PreinitArraySize)
+    }
+    /// The address range of the initialisers given by `DT_INIT_ARRAY` and `DT_INIT_ARRAYSZ`.
+    pub fn init_array(&self) -> Result<Option<Range<u32>>> {
+        self.array(DynamicTag::InitArray, DynamicTag::InitArraySize)
+    }
+    /// The address range of the finalisers given by `DT_FINI_ARRAY` and `DT_FINI_ARRAYSZ`.
+    pub fn fini_array(&self) -> Result<Option<Range<u32>>> {
+        self.array(DynamicTag::FiniArray, DynamicTag::FiniArraySize)
+    }
+}
+impl<'a> fmt::Debug for Dynamic<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.entries().into_iter().flatten().flatten())
+            .finish()
+    }
+}
+
+/// An iterator over the addresses of the packed relative relocations in a `DT_RELR` table.
+///
+/// An even word is the address of a relocation. An odd word is a bitmap whose bits 1 to 31 select the following
+/// 31 words, starting after the last address or the words covered by the previous bitmap.
+#[derive(Debug, Clone)]
+pub struct RelrIter<'a> {
+    data: &'a [u8],
+    index: u32,
+    /// The address after those covered so far, or `None` before the first address.
+    next: Option<u32>,
+    base: u32,
+    /// The bits of the current bitmap not yet yielded, shifted so bit 0 is `base`.
+    bitmap: u32
+}
+impl<'a> Iterator for RelrIter<'a> {
+    type Item = Result<u32>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.bitmap != 0 {
+                let bit = self.bitmap.trailing_zeros();
+                self.bitmap &= self.bitmap - 1;
+                return Some(Ok(self.base.wrapping_add(bit * 4)))
+            }
+            if self.index as usize >= self.data.len() / 4 {
+                return None
+            }
+            let word = match read_u32(self.data, self.index) {
+                Ok(word) => word,
+                Err(error) => return Some(Err(error))
+            };
+            self.index += 1;
+            if word & 1 == 0 {
+                self.next = Some(word.wrapping_add(4));
+                return Some(Ok(word))
+            }
+            let Some(next) = self.next else {
+                // A bitmap has nothing to follow, so stop here
+                self.data = &[];
+                return Some(Err(Error::InvalidFormat))
+            };
+            self.base = next;
+            self.bitmap = word >> 1;
+            self.next = Some(next.wrapping_add(31 * 4));
+        }
+    }
+}
+
+view!{
+    /// A dynamic table entry.
+    #[derive(Debug)]
+    pub struct Dyn {
+        pub tag: Le<DynamicTag>,
+        pub value: Le<u32>
+    }
+}
+impl Dyn {
+    pub fn new(entry: &[u8]) -> Result<&Self> {
+        Self::view(entry)
+    }
+}
+
+c_enum!{
+    pub DynamicTag(u32) {
+        Null = 0,
+        Needed = 1,
+        PltRelSize = 2,
+        PltGot = 3,
+        Hash = 4,
+        StrTab = 5,
+        SymTab = 6,
+        Rela = 7,
+        RelaSize = 8,
+        RelaEntry = 9,
+        StrSize = 10,
+        SymEntry = 11,
+        Init = 12,
+        Fini = 13,
+        SoName = 14,
+        RPath = 15,
+        Symbolic = 16,
+        Rel = 17,
+        RelSize = 18,
+        RelEntry = 19,
+        PltRel = 20,
+        Debug = 21,
+        TextRel = 22,
+        JmpRel = 23,
+        BindNow = 24,
+        InitArray = 25,
+        FiniArray = 26,
+        InitArraySize = 27,
+        FiniArraySize = 28,
+        RunPath = 29,
+        Flags = 30,
+        PreinitArray = 32,
+        PreinitArraySize = 33,
+        SymTabIndex = 34,
+        RelrSize = 35,
+        Relr = 36,
+        RelrEntry = 37,
+        GnuHash = 0x6FFFFEF5,
+        VerSym = 0x6FFFFFF0,
+        RelaCount = 0x6FFFFFF9,
+        RelCount = 0x6FFFFFFA,
+        Flags1 = 0x6FFFFFFB,
+        VerDef = 0x6FFFFFFC,
+        VerDefNum = 0x6FFFFFFD,
+        VerNeed = 0x6FFFFFFE,
+        VerNeedNum = 0x6FFFFFFF,
+        RiscVVariantCc = 0x70000001
+    } v => Err(Error::UnsupportedDynamicTag(Self(v)))
+}
+c_flags!{
+    pub DynamicFlags(u32) {
+        Origin = 0x1,
+        Symbolic = 0x2,
+        TextRel = 0x4,
+        BindNow = 0x8,
+        StaticTls = 0x10
+    } v => Err(Error::UnsupportedDynamicFlags(v))
+}
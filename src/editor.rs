@@ -0,0 +1,499 @@
+use core::mem::{size_of, size_of_val};
+use crate::{
+    Address, Elf, ElfHeader, Error, Header, Offset, Rela, Result, SectionData, SectionFlags, SectionHeader,
+    SectionSpec, SectionType, Sink, Symbol, View
+};
+
+/// Modifies an existing ELF32 file, such as linker output, and writes the result without allocating.
+///
+/// Changing the entry point and symbol values keeps every other byte of the file identical, so writing an unmodified
+/// file reproduces it exactly. Adding, removing or renaming sections re-lays-out the file: the contents of the kept
+/// sections and segments stay at their original offsets, while the section name table with the new names appended, the
+/// added sections and a new section header table are placed after them. Section indices held in section headers,
+/// symbols, `SHT_SYMTAB_SHNDX` and `SHT_GROUP` sections are renumbered to match.
+///
+/// Added sections are not covered by any segment, so they suit data that is not loaded such as debug information or
+/// metadata.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// let file = include_bytes!("../examples/test.elf");
+/// let elf = Elf::from_bytes(file)?;
+/// let mut sections = [None; 16];
+/// let mut symbols = [None; 16];
+/// let mut editor = ElfEditor::new(&elf, &mut sections, &mut symbols)?;
+///
+/// // An unmodified write is byte-identical
+/// let mut buffer = [0; 1024];
+/// let mut sink = SliceSink::new(&mut buffer);
+/// editor.write(&mut sink)?;
+/// assert_eq!(sink.written(), file);
+///
+/// let comment = editor.find_section(b".comment")?.unwrap();
+/// editor.remove_section(comment)?;
+/// let text = editor.find_section(b".text")?.unwrap();
+/// editor.rename_section(text, b".text.boot")?;
+/// let data = SectionData::Bytes(b"1.2.3");
+/// let version = editor.add_section(SectionSpec::new(b".version", SectionType::Program, SectionFlags::None, data))?;
+/// let (start, _) = elf.symbol_table()?.unwrap().find("_start")?.unwrap();
+/// editor.set_symbol_value(start, 0x10004)?;
+/// editor.set_entry(0x10004);
+///
+/// let mut sink = SliceSink::new(&mut buffer);
+/// editor.write(&mut sink)?;
+/// let edited = Elf::from_bytes(sink.written())?;
+/// assert_eq!(edited.header.entry.get().as_usize()?, 0x10004);
+/// assert_eq!(edited.section_count(), elf.section_count());
+/// assert_eq!(edited.section_name(&edited.section(text)?)?, ".text.boot");
+/// assert_eq!(edited.section(version)?.data, b"1.2.3");
+/// let (_, start) = edited.symbol_table()?.unwrap().find("_start")?.unwrap();
+/// assert_eq!((start.value().as_usize()?, start.section()), (0x10004, SymbolSection::Index(text)));
+/// # Ok(()) })().unwrap()
+/// ```
+pub struct ElfEditor<'a, 'l> {
+    elf: &'l Elf<'a>,
+    entry: u32,
+    sections: &'l mut [Option<EditedSection<'a>>],
+    len: usize,
+    symbols: &'l mut [Option<u32>],
+    relayout: bool
+}
+impl<'a, 'l> ElfEditor<'a, 'l> {
+    /// Create an editor for a parsed file.
+    ///
+    /// `sections` holds the sections of the edited file and must have an entry for every section header, plus one for
+    /// each section to be added. `symbols` holds the new value of each entry of the symbol table, and only needs to
+    /// reach the last symbol that will be changed.
+    pub fn new(
+        elf: &'l Elf<'a>,
+        sections: &'l mut [Option<EditedSection<'a>>],
+        symbols: &'l mut [Option<u32>]
+    ) -> Result<Self> {
+        let len = elf.section_count() as usize;
+        sections.get(..len).ok_or(Error::IndexOutOfRange)?;
+        sections.fill(None);
+        for (index, section) in sections[..len].iter_mut().enumerate() {
+            *section = Some(EditedSection(Edit::Original { index: index as u32, name: None }));
+        }
+        symbols.fill(None);
+        Ok(Self {
+            elf,
+            entry: elf.header.entry.get().0,
+            sections,
+            len,
+            symbols,
+            relayout: false
+        })
+    }
+    /// Returns the number of sections in the edited file.
+    pub fn section_count(&self) -> u32 {
+        self.len as u32
+    }
+    /// Find the index in the edited file of the first section called `name`.
+    pub fn find_section(&self, name: &[u8]) -> Result<Option<u32>> {
+        for index in 1..self.len {
+            if self.name(self.edit(index))? == name {
+                return Ok(Some(index as u32))
+            }
+        }
+        Ok(None)
+    }
+    /// Change the entry point address written to the file header.
+    pub fn set_entry(&mut self, entry: u32) {
+        self.entry = entry;
+    }
+    /// Change the value of the symbol at `index` in the symbol table.
+    pub fn set_symbol_value(&mut self, index: u32, value: u32) -> Result<()> {
+        let symbols = self.elf.symbol_table()?.ok_or(Error::IndexOutOfRange)?;
+        if index >= symbols.len() {
+            return Err(Error::IndexOutOfRange)
+        }
+        *self.symbols.get_mut(index as usize).ok_or(Error::IndexOutOfRange)? = Some(value);
+        Ok(())
+    }
+    /// Rename the section at `index` in the edited file.
+    pub fn rename_section(&mut self, index: u32, name: &'a [u8]) -> Result<()> {
+        self.elf.section_names.ok_or(Error::MissingSectionNames)?;
+        match self.edit_mut(index)? {
+            Edit::Original { name: old, .. } => *old = Some(name),
+            Edit::Added(section) => section.name = name
+        }
+        self.relayout = true;
+        Ok(())
+    }
+    /// Remove the section at `index` in the edited file, renumbering the sections after it.
+    ///
+    /// Writing fails with `Error::SectionInUse` if a remaining section or symbol still refers to it.
+    pub fn remove_section(&mut self, index: u32) -> Result<()> {
+        self.elf.section_names.ok_or(Error::MissingSectionNames)?;
+        if let Edit::Original { index: original, .. } = *self.edit_mut(index)? {
+            if original == 0 || original == self.elf.header.section_name_index(self.elf.data)? {
+                return Err(Error::SectionInUse(original))
+            }
+        }
+        let index = index as usize;
+        self.sections[index..self.len].rotate_left(1);
+        self.len -= 1;
+        self.sections[self.len] = None;
+        self.relayout = true;
+        Ok(())
+    }
+    /// Append a section, returning its index in the edited file.
+    ///
+    /// Relocation sections are linked to the symbol table. Symbol tables cannot be added.
+    pub fn add_section(&mut self, section: SectionSpec<'a>) -> Result<u32> {
+        self.elf.section_names.ok_or(Error::MissingSectionNames)?;
+        if let SectionData::Symbols(_) = section.data {
+            return Err(Error::InvalidFormat)
+        }
+        let slot = self.sections.get_mut(self.len).ok_or(Error::BufferTooSmall)?;
+        *slot = Some(EditedSection(Edit::Added(section)));
+        self.len += 1;
+        self.relayout = true;
+        Ok(self.len as u32 - 1)
+    }
+    /// Returns the size of the edited file in bytes.
+    pub fn size(&self) -> Result<usize> {
+        Ok(self.layout(|_, _, _| Ok(()))?.end)
+    }
+    /// Write the edited file to `sink`, returning its size in bytes.
+    pub fn write(&self, sink: &mut impl Sink) -> Result<usize> {
+        let data = self.elf.data;
+        let layout = self.layout(|_, _, _| Ok(()))?;
+        let original = self.elf.header;
+        let mut header = Header {
+            entry: Address(self.entry).into(),
+            ..*original
+        };
+        if self.relayout {
+            let names = self.new_index(original.section_name_index(data)?)?;
+            header.sh_offset = Offset(to_u32(layout.table)?).into();
+            header.sh_entry_size = (size_of::<SectionHeader>() as u16).into();
+            header.sh_count = (if self.len < 0xFF00 { self.len as u16 } else { 0 }).into();
+            header.section_name_table = (if names < 0xFF00 { names as u16 } else { 0xFFFF }).into();
+        }
+        sink.write(header.as_bytes())?;
+
+        // Copy the original bytes, replacing the contents of the index and symbol tables
+        let mut position = size_of::<Header>();
+        while let Some((index, offset)) = self.next_rewritten(position, layout.prefix)? {
+            sink.write(&data[position..offset])?;
+            position = offset + self.rewrite(sink, index)?;
+        }
+        sink.write(&data[position..layout.prefix])?;
+        if !self.relayout {
+            return Ok(layout.end)
+        }
+
+        sink.zeros(layout.names - layout.prefix)?;
+        let names = self.elf.section_names.ok_or(Error::MissingSectionNames)?;
+        sink.write(names.0)?;
+        for index in 1..self.len {
+            if let Some(name) = self.new_name(self.edit(index)) {
+                sink.write(name)?;
+                sink.write(&[0])?;
+            }
+        }
+        let mut position = layout.names + layout.names_size;
+        self.layout(|_, section, offset| {
+            sink.zeros(offset - position)?;
+            position = offset + file_size(section);
+            match section.data {
+                SectionData::Bytes(data) => sink.write(data),
+                SectionData::Relocations(relocations) => {
+                    relocations.iter().try_for_each(|relocation| sink.write(relocation.as_bytes()))
+                }
+                _ => Ok(())
+            }
+        })?;
+        sink.zeros(layout.table - position)?;
+
+        let (count, names) = (self.len, self.new_index(original.section_name_index(data)?)?);
+        // Section header 0 holds the counts that do not fit in the file header
+        let null = self.elf.section(0)?.header;
+        sink.write(SectionHeader {
+            size: (if count < 0xFF00 { 0 } else { count as u32 }).into(),
+            link: (if names < 0xFF00 { 0 } else { names }).into(),
+            ..*null
+        }.as_bytes())?;
+        let mut name = self.elf.section_names.ok_or(Error::MissingSectionNames)?.len();
+        let mut added = layout.names + layout.names_size;
+        for index in 1..count {
+            let edit = self.edit(index);
+            let header = match edit {
+                Edit::Original { index: original, .. } => {
+                    let section = self.elf.section(original)?;
+                    let header = section.header;
+                    let ty = header.ty.get();
+                    let is_names = original == original_names(self.elf)?;
+                    let info = header.info.get();
+                    let is_relocation = matches!(ty, SectionType::Rel | SectionType::Rela);
+                    let info_is_index = is_relocation || header.flags.get().all(SectionFlags::InfoLink);
+                    SectionHeader {
+                        name: if self.new_name(edit).is_some() { to_u32(name)?.into() } else { header.name },
+                        offset: if is_names { Offset(to_u32(layout.names)?).into() } else { header.offset },
+                        size: if is_names { to_u32(layout.names_size)?.into() } else { header.size },
+                        link: self.new_index(header.link.get())?.into(),
+                        info: if info_is_index { self.new_index(info)?.into() } else { header.info },
+                        ..*header
+                    }
+                }
+                Edit::Added(section) => {
+                    let alignment = section.alignment.max(1) as usize;
+                    let offset = align_up(added, alignment)?;
+                    added = offset + file_size(&section);
+                    let (link, entry_size) = match section.data {
+                        SectionData::Relocations(_) => (self.symbol_table_index()?, size_of::<Rela>() as u32),
+                        _ => (section.link, section.entry_size)
+                    };
+                    SectionHeader {
+                        name: to_u32(name)?.into(),
+                        ty: section.ty.into(),
+                        flags: section.flags.into(),
+                        address: Address(section.address).into(),
+                        offset: Offset(to_u32(offset)?).into(),
+                        size: to_u32(mem_size(&section))?.into(),
+                        link: link.into(),
+                        info: section.info.into(),
+                        alignment: section.alignment.into(),
+                        entry_size: entry_size.into()
+                    }
+                }
+            };
+            name += self.new_name(edit).map_or(0, |name| name.len() + 1);
+            sink.write(header.as_bytes())?;
+        }
+        Ok(layout.end)
+    }
+
+    fn edit(&self, index: usize) -> Edit<'a> {
+        // Every slot below `len` is filled
+        self.sections[index].map_or(Edit::Original { index: 0, name: None }, |section| section.0)
+    }
+    fn edit_mut(&mut self, index: u32) -> Result<&mut Edit<'a>> {
+        let index = index as usize;
+        if index == 0 || index >= self.len {
+            return Err(Error::IndexOutOfRange)
+        }
+        self.sections[index].as_mut().map(|section| &mut section.0).ok_or(Error::IndexOutOfRange)
+    }
+    fn name(&self, edit: Edit<'a>) -> Result<&'a [u8]> {
+        match edit {
+            Edit::Original { name: Some(name), .. } => Ok(name),
+            Edit::Original { index, .. } => {
+                let section = self.elf.section(index)?;
+                self.elf.section_names.ok_or(Error::MissingSectionNames)?.get_bytes(section.header.name.get())
+            }
+            Edit::Added(section) => Ok(section.name)
+        }
+    }
+    /// The name of a renamed or added section, which is appended to the section name table.
+    fn new_name(&self, edit: Edit<'a>) -> Option<&'a [u8]> {
+        match edit {
+            Edit::Original { name, .. } => name,
+            Edit::Added(section) => Some(section.name)
+        }
+    }
+    /// The index in the edited file of the original section at `original`.
+    fn new_index(&self, original: u32) -> Result<u32> {
+        if original == 0 || !self.relayout {
+            return Ok(original)
+        }
+        (1..self.len)
+            .find(|&index| matches!(self.edit(index), Edit::Original { index, .. } if index == original))
+            .map(|index| index as u32)
+            .ok_or(Error::SectionInUse(original))
+    }
+    /// The index in the edited file of the symbol table.
+    fn symbol_table_index(&self) -> Result<u32> {
+        for index in 1..self.len {
+            if let Edit::Original { index: original, .. } = self.edit(index) {
+                if self.elf.section(original)?.header.ty.get() == SectionType::SymbolTable {
+                    return Ok(index as u32)
+                }
+            }
+        }
+        Ok(0)
+    }
+    /// Returns true if the contents of the original section at `index` are written by `rewrite`.
+    fn is_rewritten(&self, index: u32) -> Result<bool> {
+        let ty = self.elf.section(index)?.header.ty.get();
+        Ok(match ty {
+            SectionType::SymbolTable => self.relayout || self.symbols.iter().any(Option::is_some),
+            SectionType::DynamicSymbolTable | SectionType::SymbolIndex | SectionType::Group => self.relayout,
+            _ => false
+        })
+    }
+    /// Find the kept section with rewritten contents that starts first at or after `position` and ends by `end`,
+    /// returning its original index and offset.
+    fn next_rewritten(&self, position: usize, end: usize) -> Result<Option<(u32, usize)>> {
+        let mut next: Option<(u32, usize)> = None;
+        for index in 1..self.len {
+            let Edit::Original { index, .. } = self.edit(index) else { continue };
+            if !self.is_rewritten(index)? {
+                continue
+            }
+            let header = self.elf.section(index)?.header;
+            let offset = header.offset.get().as_usize()?;
+            let size = header.size.get() as usize;
+            if offset < size_of::<Header>() {
+                // The contents would overlap the file header, which is written separately
+                return Err(Error::InvalidFormat)
+            }
+            if offset >= position && offset + size <= end && next.is_none_or(|(_, next)| offset < next) {
+                next = Some((index, offset));
+            }
+        }
+        Ok(next)
+    }
+    /// Write the contents of the original section at `index` with section indices renumbered and symbol values
+    /// changed, returning the number of bytes written.
+    fn rewrite(&self, sink: &mut impl Sink, index: u32) -> Result<usize> {
+        let section = self.elf.section(index)?;
+        let data = section.data;
+        match section.header.ty.get() {
+            SectionType::SymbolTable | SectionType::DynamicSymbolTable => {
+                let is_symbol_table = section.header.ty.get() == SectionType::SymbolTable;
+                let entry_size = section.header.entry_size.get() as usize;
+                if entry_size < size_of::<Symbol>() {
+                    return Err(Error::InvalidEntrySize)
+                }
+                let mut chunks = data.chunks_exact(entry_size);
+                for (number, entry) in chunks.by_ref().enumerate() {
+                    let symbol = Symbol::view(entry)?;
+                    let value = self.symbols.get(number).copied().flatten().filter(|_| is_symbol_table);
+                    let section_index = match symbol.section_index.get() {
+                        index @ 1..0xFF00 => self.new_index(index.into())? as u16,
+                        index => index
+                    };
+                    sink.write(Symbol {
+                        value: value.map_or(symbol.value, |value| Address(value).into()),
+                        section_index: section_index.into(),
+                        ..*symbol
+                    }.as_bytes())?;
+                    sink.write(&entry[size_of::<Symbol>()..])?;
+                }
+                sink.write(chunks.remainder())?;
+            }
+            ty => {
+                // Group sections start with a flags word
+                let skip = if ty == SectionType::Group { 1 } else { 0 };
+                let mut words = data.chunks_exact(4);
+                for (number, word) in words.by_ref().enumerate() {
+                    let word = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+                    let word = if number < skip { word } else { self.new_index(word)? };
+                    sink.write(&word.to_le_bytes())?;
+                }
+                sink.write(words.remainder())?;
+            }
+        }
+        Ok(data.len())
+    }
+    /// Call `place` with the index, description and offset of each added section, returning the layout of the file.
+    fn layout(&self, mut place: impl FnMut(usize, &SectionSpec<'a>, usize) -> Result<()>) -> Result<Layout> {
+        let data = self.elf.data;
+        if !self.relayout {
+            return Ok(Layout {
+                prefix: data.len(),
+                names: data.len(),
+                names_size: 0,
+                table: data.len(),
+                end: data.len()
+            })
+        }
+        // Everything that is kept in place: the headers, the segments and the remaining sections
+        let header = self.elf.header;
+        let mut prefix = size_of::<Header>();
+        if self.elf.program_count() != 0 {
+            let size = self.elf.program_count() as usize * header.ph_entry_size.get() as usize;
+            prefix = prefix.max(header.ph_offset.get().as_usize()? + size);
+        }
+        for program in self.elf.programs()? {
+            let program = program?;
+            prefix = prefix.max(program.header.offset.get().as_usize()? + program.data.len());
+        }
+        let names_index = original_names(self.elf)?;
+        let mut names_size = self.elf.section_names.ok_or(Error::MissingSectionNames)?.len();
+        for index in 1..self.len {
+            let edit = self.edit(index);
+            names_size += self.new_name(edit).map_or(0, |name| name.len() + 1);
+            if let Edit::Original { index, .. } = edit {
+                let section = self.elf.section(index)?;
+                if index != names_index && !section.data.is_empty() {
+                    prefix = prefix.max(section.header.offset.get().as_usize()? + section.data.len());
+                }
+            }
+        }
+        let names = prefix;
+        let mut cursor = names + names_size;
+        for index in 1..self.len {
+            if let Edit::Added(section) = self.edit(index) {
+                let offset = align_up(cursor, section.alignment.max(1) as usize)?;
+                place(index, &section, offset)?;
+                cursor = offset + file_size(&section);
+            }
+        }
+        let table = align_up(cursor, 4)?;
+        Ok(Layout {
+            prefix,
+            names,
+            names_size,
+            table,
+            end: table + self.len * size_of::<SectionHeader>()
+        })
+    }
+}
+
+/// A section of a file being edited by an [`ElfEditor`].
+#[derive(Debug, Clone, Copy)]
+pub struct EditedSection<'a>(Edit<'a>);
+
+#[derive(Debug, Clone, Copy)]
+enum Edit<'a> {
+    /// The section at `index` in the original file, with a new name if renamed.
+    Original { index: u32, name: Option<&'a [u8]> },
+    Added(SectionSpec<'a>)
+}
+
+/// The offsets of the parts of an edited file.
+struct Layout {
+    /// The end of the original bytes that are kept.
+    prefix: usize,
+    /// The offset of the new section name table.
+    names: usize,
+    names_size: usize,
+    /// The offset of the section header table.
+    table: usize,
+    end: usize
+}
+
+fn original_names(elf: &Elf) -> Result<u32> {
+    elf.header.section_name_index(elf.data)
+}
+
+fn file_size(section: &SectionSpec) -> usize {
+    match section.data {
+        SectionData::Bytes(data) => data.len(),
+        SectionData::Relocations(relocations) => size_of_val(relocations),
+        _ => 0
+    }
+}
+
+fn mem_size(section: &SectionSpec) -> usize {
+    match section.data {
+        SectionData::NoBits(size) => size as usize,
+        _ => file_size(section)
+    }
+}
+
+fn align_up(offset: usize, alignment: usize) -> Result<usize> {
+    if !alignment.is_power_of_two() {
+        return Err(Error::Unaligned)
+    }
+    Ok(offset.checked_add(alignment - 1).ok_or(Error::IntegerOverflow)? & !(alignment - 1))
+}
+
+fn to_u32(value: usize) -> Result<u32> {
+    u32::try_from(value).map_err(|_| Error::IntegerOverflow)
+}
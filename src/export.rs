@@ -0,0 +1,236 @@
+use core::ops::Range;
+use crate::{Elf, Error, Program, ProgramType, Result, Sink};
+
+/// Converts the `PT_LOAD` segments of an executable into a flat binary, Intel HEX or Motorola S-record image for
+/// flashing, like `objcopy -O binary`, `-O ihex` and `-O srec`.
+///
+/// Only the bytes held in the file are exported, so a trailing `.bss` is left out. Segments are placed at their load
+/// or run address as chosen by `ImageAddress`, and must not overlap.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// let elf = Elf::from_bytes(include_bytes!("../examples/test.elf"))?;
+/// let exporter = Exporter::new(&elf, ImageAddress::Load, Some(0xFF));
+/// assert_eq!(exporter.range()?, Some(0x10000..0x110E0));
+///
+/// let mut buffer = [0; 0x2000];
+/// let mut sink = SliceSink::new(&mut buffer);
+/// exporter.write_binary(&mut sink)?;
+/// let image = sink.written();
+/// assert_eq!(image.len(), 0x10E0);
+/// assert_eq!(&image[0xB4..0xC2], b"Hello, World!\n");
+/// assert_eq!(image[0xC2..0x10C2], [0xFF; 0x1000]);
+///
+/// let mut sink = SliceSink::new(&mut buffer);
+/// Exporter::new(&elf, ImageAddress::Load, None).write_intel_hex(&mut sink)?;
+/// let hex = core::str::from_utf8(sink.written()).unwrap();
+/// assert!(hex.starts_with(":020000040001F9\r\n:100000007F454C46"));
+/// assert!(hex.ends_with(":0E10D2007300000001459308D0057300000074\r\n:04000005000110C224\r\n:00000001FF\r\n"));
+///
+/// let mut sink = SliceSink::new(&mut buffer);
+/// Exporter::new(&elf, ImageAddress::Load, None).write_srec(&mut sink)?;
+/// let srec = core::str::from_utf8(sink.written()).unwrap();
+/// assert!(srec.starts_with("S0030000FC\r\nS315000100007F454C46"));
+/// assert!(srec.ends_with("S313000110D27300000001459308D005730000006D\r\nS705000110C227\r\n"));
+/// # Ok(()) })().unwrap()
+/// ```
+#[derive(Debug)]
+pub struct Exporter<'a, 'e> {
+    elf: &'e Elf<'a>,
+    address: ImageAddress,
+    fill: Option<u8>
+}
+impl<'a, 'e> Exporter<'a, 'e> {
+    /// Create an exporter placing segments at `address`.
+    ///
+    /// Gaps between segments are filled with `fill`. Without one, the flat binary is zero-filled and the Intel HEX and
+    /// S-record images leave the gaps out.
+    pub fn new(elf: &'e Elf<'a>, address: ImageAddress, fill: Option<u8>) -> Self {
+        Self {
+            elf,
+            address,
+            fill
+        }
+    }
+    /// Returns the range of addresses covered by the image, or `None` if no segment holds any data.
+    pub fn range(&self) -> Result<Option<Range<u32>>> {
+        let mut range: Option<Range<u32>> = None;
+        self.blocks(None, |address, data| {
+            let end = address + data.len() as u32;
+            range = Some(range.as_ref().map_or(address, |range| range.start)..end);
+            Ok(())
+        })?;
+        Ok(range)
+    }
+    /// Write the image as a flat binary starting at the lowest address, returning its size in bytes.
+    pub fn write_binary(&self, sink: &mut impl Sink) -> Result<usize> {
+        let mut size = 0;
+        self.blocks(Some(self.fill.unwrap_or(0)), |_, data| {
+            size += data.len();
+            sink.write(data)
+        })?;
+        Ok(size)
+    }
+    /// Write the image as Intel HEX, with extended linear address records above 64KiB and a start linear address
+    /// record holding the entry point, returning the number of bytes written.
+    pub fn write_intel_hex(&self, sink: &mut impl Sink) -> Result<usize> {
+        let mut size = 0;
+        let mut upper = 0;
+        self.blocks(self.fill, |address, data| {
+            for (address, data) in records(address, data, |address| 0x10000 - (address & 0xFFFF) as usize) {
+                if address >> 16 != upper {
+                    upper = address >> 16;
+                    size += intel_hex(sink, 0x04, 0, &(upper as u16).to_be_bytes())?;
+                }
+                size += intel_hex(sink, 0x00, address as u16, data)?;
+            }
+            Ok(())
+        })?;
+        size += intel_hex(sink, 0x05, 0, &self.elf.header.entry.get().0.to_be_bytes())?;
+        size += intel_hex(sink, 0x01, 0, &[])?;
+        Ok(size)
+    }
+    /// Write the image as Motorola S-records, using S3 records with 32-bit addresses and an S7 record holding the
+    /// entry point, returning the number of bytes written.
+    pub fn write_srec(&self, sink: &mut impl Sink) -> Result<usize> {
+        let mut size = srec(sink, b'0', &[0, 0])?;
+        self.blocks(self.fill, |address, data| {
+            for (address, data) in records(address, data, |_| usize::MAX) {
+                let mut record = [0; 4 + RECORD_SIZE];
+                record[..4].copy_from_slice(&address.to_be_bytes());
+                record[4..][..data.len()].copy_from_slice(data);
+                size += srec(sink, b'3', &record[..4 + data.len()])?;
+            }
+            Ok(())
+        })?;
+        size += srec(sink, b'7', &self.elf.header.entry.get().0.to_be_bytes())?;
+        Ok(size)
+    }
+
+    fn address(&self, program: &Program<'a>) -> u32 {
+        match self.address {
+            ImageAddress::Load => program.header.phys_addr.get().0,
+            ImageAddress::Run => program.header.virt_addr.get().0
+        }
+    }
+    /// Call `block` with the data of each segment in address order, and with `fill` bytes for the gaps between them.
+    pub(crate) fn blocks(&self, fill: Option<u8>, mut block: impl FnMut(u32, &[u8]) -> Result<()>) -> Result<()> {
+        // The address and index of the last segment, to find the next in order, and the end of its data
+        let mut previous: Option<((u32, usize), u64)> = None;
+        loop {
+            let mut next: Option<((u32, usize), Program<'a>)> = None;
+            for (index, program) in self.elf.programs()?.enumerate() {
+                let program = program?;
+                if program.header.ty.get() != ProgramType::Load || program.data.is_empty() {
+                    continue
+                }
+                let key = (self.address(&program), index);
+                if previous.is_some_and(|(previous, _)| key <= previous) || next.is_some_and(|(next, _)| key >= next) {
+                    continue
+                }
+                next = Some((key, program));
+            }
+            let Some((key, program)) = next else { return Ok(()) };
+            let address = key.0;
+            let end = u64::from(address) + program.data.len() as u64;
+            if end > u32::MAX.into() {
+                return Err(Error::IntegerOverflow)
+            }
+            if let Some((_, previous_end)) = previous {
+                if u64::from(address) < previous_end {
+                    return Err(Error::SegmentOverlap { address })
+                }
+                if let Some(fill) = fill {
+                    let chunk = [fill; 64];
+                    let mut position = previous_end as u32;
+                    while position < address {
+                        let size = (address - position).min(chunk.len() as u32);
+                        block(position, &chunk[..size as usize])?;
+                        position += size;
+                    }
+                }
+            }
+            block(address, program.data)?;
+            previous = Some((key, end));
+        }
+    }
+}
+
+/// The address each segment is placed at in an exported image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageAddress {
+    /// `phys_addr`, where the segment is stored, as used by `objcopy`.
+    Load,
+    /// `virt_addr`, where the segment runs.
+    Run
+}
+
+/// The number of data bytes in each Intel HEX and S-record record.
+const RECORD_SIZE: usize = 16;
+
+/// Split `data` at `address` into records of up to `RECORD_SIZE` bytes, which also end where `limit` allows.
+fn records(address: u32, data: &[u8], limit: impl Fn(u32) -> usize) -> impl Iterator<Item = (u32, &[u8])> {
+    let mut offset = 0;
+    core::iter::from_fn(move || {
+        if offset == data.len() {
+            return None
+        }
+        let start = address.wrapping_add(offset as u32);
+        let size = (data.len() - offset).min(RECORD_SIZE).min(limit(start));
+        let record = (start, &data[offset..offset + size]);
+        offset += size;
+        Some(record)
+    })
+}
+
+/// A line of text being built from hexadecimal bytes.
+struct Line {
+    text: [u8; 64],
+    len: usize
+}
+impl Line {
+    fn new(start: &[u8]) -> Self {
+        let mut line = Self { text: [0; 64], len: start.len() };
+        line.text[..start.len()].copy_from_slice(start);
+        line
+    }
+    fn push(&mut self, byte: u8) {
+        const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+        self.text[self.len] = DIGITS[usize::from(byte >> 4)];
+        self.text[self.len + 1] = DIGITS[usize::from(byte & 0xF)];
+        self.len += 2;
+    }
+    fn finish(mut self, sink: &mut impl Sink) -> Result<usize> {
+        self.text[self.len..][..2].copy_from_slice(b"\r\n");
+        sink.write(&self.text[..self.len + 2])?;
+        Ok(self.len + 2)
+    }
+}
+
+/// Write an Intel HEX record, which ends with the two's complement of the sum of its bytes.
+fn intel_hex(sink: &mut impl Sink, ty: u8, address: u16, data: &[u8]) -> Result<usize> {
+    let mut line = Line::new(b":");
+    let [high, low] = address.to_be_bytes();
+    let mut sum = 0u8;
+    for &byte in [data.len() as u8, high, low, ty].iter().chain(data) {
+        line.push(byte);
+        sum = sum.wrapping_add(byte);
+    }
+    line.push(sum.wrapping_neg());
+    line.finish(sink)
+}
+
+/// Write an S-record of type `ty`, which counts the bytes that follow and ends with the ones' complement of their sum.
+fn srec(sink: &mut impl Sink, ty: u8, data: &[u8]) -> Result<usize> {
+    let mut line = Line::new(&[b'S', ty]);
+    let count = data.len() as u8 + 1;
+    let mut sum = count;
+    line.push(count);
+    for &byte in data {
+        line.push(byte);
+        sum = sum.wrapping_add(byte);
+    }
+    line.push(!sum);
+    line.finish(sink)
+}
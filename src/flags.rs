@@ -0,0 +1,82 @@
+use crate::{Class, Elf, ElfHeader, Error, Result};
+
+impl<'a, C: Class> Elf<'a, C> {
+    /// Check that the file uses the calling convention for floating-point arguments given by `abi`.
+    ///
+    /// Code built for different float ABIs cannot call each other, so a soft-float kernel should refuse modules built
+    /// for a hard-float ABI.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// # let mut data = [0u32; 256];
+    /// # let elf = include_bytes!("../examples/attributes.elf");
+    /// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+    /// let elf = Elf::new(&data)?;
+    /// let flags = elf.header.riscv_flags();
+    /// assert_eq!(flags.float_abi(), FloatAbi::Double);
+    /// assert!(flags.all(RiscVFlags::Rvc));
+    /// assert!(!flags.any(RiscVFlags::Rve | RiscVFlags::Tso));
+    /// assert!(matches!(
+    ///     elf.check_float_abi(FloatAbi::Soft),
+    ///     Err(Error::WrongFloatAbi { expected: FloatAbi::Soft, actual: FloatAbi::Double })
+    /// ));
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn check_float_abi(&self, abi: FloatAbi) -> Result<()> {
+        let actual = self.header.riscv_flags().float_abi();
+        if actual != abi {
+            Err(Error::WrongFloatAbi { expected: abi, actual })
+        } else {
+            Ok(())
+        }
+    }
+    /// Check that code from this file can be linked with or called from code built with `flags`.
+    ///
+    /// The float ABI and the use of the RV32E register convention must match. Compressed instructions and the TSO
+    /// memory model do not affect the calling convention so are not compared.
+    pub fn check_abi_compatible(&self, flags: RiscVFlags) -> Result<()> {
+        let actual = self.header.riscv_flags();
+        actual.validate()?;
+        if actual.float_abi() != flags.float_abi() {
+            return Err(Error::WrongFloatAbi { expected: flags.float_abi(), actual: actual.float_abi() })
+        }
+        if actual.all(RiscVFlags::Rve) != flags.all(RiscVFlags::Rve) {
+            return Err(Error::WrongRiscVFlags { expected: flags & RiscVFlags::Rve, actual: actual & RiscVFlags::Rve })
+        }
+        Ok(())
+    }
+}
+
+c_flags!{
+    pub RiscVFlags(u32) {
+        Rvc = 0x1,
+        FloatAbiSingle = 0x2,
+        FloatAbiDouble = 0x4,
+        Rve = 0x8,
+        Tso = 0x10
+    } v => Err(Error::UnsupportedRiscVFlags(v))
+}
+impl RiscVFlags {
+    /// Both float ABI bits, which together select the quad-float ABI.
+    #[allow(non_upper_case_globals)]
+    pub const FloatAbiQuad: Self = Self(0x6);
+
+    /// The calling convention used for floating-point arguments.
+    pub fn float_abi(self) -> FloatAbi {
+        FloatAbi((self.0 & Self::FloatAbiQuad.0) >> 1)
+    }
+    /// Returns an error if any unknown bits are set.
+    pub fn validate(self) -> Result<()> {
+        Self::try_from(self.0).map(|_| ())
+    }
+}
+
+c_enum!{
+    pub FloatAbi(u32) {
+        Soft = 0,
+        Single = 1,
+        Double = 2,
+        Quad = 3
+    } v => Err(Error::UnsupportedFloatAbi(Self(v)))
+}
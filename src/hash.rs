@@ -0,0 +1,265 @@
+use core::{mem::size_of, fmt};
+use crate::{
+    bytes::read_u32, Class, Elf, ElfSectionHeader, ElfSymbol, Error, Result, Section, SectionType, SymbolTable
+};
+
+impl<'a, C: Class> Elf<'a, C> {
+    /// Find a dynamic symbol defined by the file by name, returning it with its index in the dynamic symbol table.
+    ///
+    /// The `SHT_GNU_HASH` table is used if the file has one, then the `SHT_HASH` table, so only the symbols sharing a
+    /// hash bucket with `name` are compared. Without either, the dynamic symbol table is scanned. Undefined symbols,
+    /// which the GNU table leaves out, are never returned. Use `Dynamic::lookup_symbol` for files without section
+    /// headers.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// let elf = Elf::from_bytes(include_bytes!("../examples/libdynamic.so"))?;
+    /// let (index, version) = elf.lookup_symbol("version")?.unwrap();
+    /// assert_eq!(version.value.get().as_usize()?, 0x3340);
+    /// assert_eq!(elf.dynamic_symbol_table()?.unwrap().find("version")?.unwrap().0, index);
+    /// assert!(elf.lookup_symbol("missing")?.is_none());
+    /// assert!(elf.lookup_symbol("puts")?.is_none());
+    ///
+    /// // Each table finds the same symbols
+    /// let symbols = elf.dynamic_symbol_table()?.unwrap();
+    /// let gnu = GnuHashTable::from_section(elf.find_section(SectionType::GnuHash)?.unwrap())?;
+    /// let sysv = HashTable::from_section(elf.find_section(SectionType::HashTable)?.unwrap())?;
+    /// assert_eq!(sysv.symbol_count(), symbols.len());
+    /// for name in ["add", "greet", "version", "puts"] {
+    ///     let (index, _) = symbols.find(name)?.unwrap();
+    ///     assert_eq!(sysv.find(&symbols, name)?.unwrap().0, index);
+    ///     // The GNU table only holds defined symbols
+    ///     let defined = !symbols.get(index)?.is_undefined();
+    ///     assert_eq!(gnu.find(&symbols, name)?.map(|(index, _)| index), defined.then_some(index));
+    /// }
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn lookup_symbol(&self, name: &str) -> Result<Option<(u32, &'a C::Symbol)>> {
+        let mut gnu = None;
+        let mut sysv = None;
+        for section in self.sections()? {
+            let section = section?;
+            match section.header.ty() {
+                SectionType::GnuHash if gnu.is_none() => gnu = Some(section),
+                SectionType::HashTable if sysv.is_none() => sysv = Some(section),
+                _ => ()
+            }
+        }
+        let found = if let Some(section) = gnu {
+            let symbols = SymbolTable::new(self, self.section(section.header.link())?)?;
+            GnuHashTable::from_section(section)?.find(&symbols, name)?
+        } else if let Some(section) = sysv {
+            let symbols = SymbolTable::new(self, self.section(section.header.link())?)?;
+            HashTable::from_section(section)?.find(&symbols, name)?
+        } else {
+            match self.dynamic_symbol_table()? {
+                Some(symbols) => symbols.find(name)?,
+                None => None
+            }
+        };
+        Ok(found.filter(|(_, symbol)| !symbol.is_undefined()))
+    }
+}
+
+/// A view of a SysV `SHT_HASH` section or `DT_HASH` table, which chains the symbols of a symbol table by the hash of
+/// their names.
+#[derive(Clone, Copy)]
+pub struct HashTable<'a> {
+    buckets: &'a [u8],
+    chains: &'a [u8]
+}
+impl<'a> HashTable<'a> {
+    /// Coerce a `SHT_HASH` section into a hash table.
+    pub fn from_section<C: Class>(section: Section<'a, C>) -> Result<Self> {
+        section.check_type(SectionType::HashTable)?;
+        Self::new(section.data)
+    }
+    /// Create a hash table from raw words: `nbucket`, `nchain`, then the buckets and chains. Any data after the
+    /// chains is ignored.
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        let buckets = words(data, 2, read_u32(data, 0)?)?;
+        let chains = words(&data[8 + buckets.len()..], 0, read_u32(data, 1)?)?;
+        Ok(Self {
+            buckets,
+            chains
+        })
+    }
+    /// The SysV hash of a symbol name.
+    pub fn hash(name: &[u8]) -> u32 {
+        let mut hash = 0u32;
+        for &byte in name {
+            hash = (hash << 4).wrapping_add(byte.into());
+            let high = hash & 0xF000_0000;
+            hash ^= high >> 24;
+            hash &= !high;
+        }
+        hash
+    }
+    /// Returns the number of buckets.
+    pub fn bucket_count(&self) -> u32 {
+        (self.buckets.len() / 4) as u32
+    }
+    /// Returns the number of symbols in the symbol table, which equals the number of chains.
+    pub fn symbol_count(&self) -> u32 {
+        (self.chains.len() / 4) as u32
+    }
+    /// Find the symbol with the given name in `symbols`, the table this hash table was built for.
+    pub fn find<C: Class>(&self, symbols: &SymbolTable<'a, C>, name: &str) -> Result<Option<(u32, &'a C::Symbol)>> {
+        if self.buckets.is_empty() {
+            return Ok(None)
+        }
+        let mut index = read_u32(self.buckets, Self::hash(name.as_bytes()) % self.bucket_count())?;
+        // A chain visits each symbol at most once, so a longer one must loop
+        for _ in 0..self.symbol_count() {
+            if index == 0 {
+                return Ok(None)
+            }
+            let symbol = symbols.get(index)?;
+            if has_name(symbols, symbol, name)? {
+                return Ok(Some((index, symbol)))
+            }
+            index = read_u32(self.chains, index)?;
+        }
+        if index == 0 { Ok(None) } else { Err(Error::InvalidFormat) }
+    }
+}
+impl<'a> fmt::Debug for HashTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashTable")
+            .field("buckets", &self.bucket_count())
+            .field("chains", &self.symbol_count())
+            .finish()
+    }
+}
+
+/// A view of a `SHT_GNU_HASH` section or `DT_GNU_HASH` table.
+///
+/// Only the symbols from `symbol_offset` onwards are hashed, sorted so that each bucket holds a run of symbols. A bloom
+/// filter rejects most names that are not present without touching the symbol table.
+#[derive(Clone, Copy)]
+pub struct GnuHashTable<'a> {
+    symbol_offset: u32,
+    bloom_shift: u32,
+    /// The size in bytes of each bloom filter word, which is the size of an address in the file's class.
+    bloom_word_size: usize,
+    bloom: &'a [u8],
+    buckets: &'a [u8],
+    /// The hashes of the symbols from `symbol_offset`, with the lowest bit set on the last of each bucket.
+    chains: &'a [u8]
+}
+impl<'a> GnuHashTable<'a> {
+    /// Coerce a `SHT_GNU_HASH` section into a hash table.
+    pub fn from_section<C: Class>(section: Section<'a, C>) -> Result<Self> {
+        section.check_type(SectionType::GnuHash)?;
+        Self::new::<C>(section.data)
+    }
+    /// Create a hash table from raw data for a file of class `C`.
+    ///
+    /// The table does not record the number of chains, so they are taken to run to the end of `data`.
+    pub fn new<C: Class>(data: &'a [u8]) -> Result<Self> {
+        let bucket_count = read_u32(data, 0)?;
+        let bloom_count = read_u32(data, 2)?;
+        if bloom_count == 0 {
+            return Err(Error::InvalidFormat)
+        }
+        let bloom_word_size = size_of::<C::Word>();
+        let bloom_size = (bloom_count as usize).checked_mul(bloom_word_size).ok_or(Error::IntegerOverflow)?;
+        let bloom = data.get(16..).and_then(|data| data.get(..bloom_size)).ok_or(Error::UnexpectedEoF)?;
+        let rest = &data[16 + bloom_size..];
+        let buckets = words(rest, 0, bucket_count)?;
+        Ok(Self {
+            symbol_offset: read_u32(data, 1)?,
+            bloom_shift: read_u32(data, 3)?,
+            bloom_word_size,
+            bloom,
+            buckets,
+            chains: &rest[buckets.len()..]
+        })
+    }
+    /// The GNU hash of a symbol name.
+    pub fn hash(name: &[u8]) -> u32 {
+        name.iter().fold(5381u32, |hash, &byte| hash.wrapping_mul(33).wrapping_add(byte.into()))
+    }
+    /// Returns the number of buckets.
+    pub fn bucket_count(&self) -> u32 {
+        (self.buckets.len() / 4) as u32
+    }
+    /// Returns the index of the first symbol in the hash table. Symbols before it can only be found by scanning.
+    pub fn symbol_offset(&self) -> u32 {
+        self.symbol_offset
+    }
+    /// Count the symbols in the symbol table by walking to the end of the chain of the last bucket.
+    pub fn symbol_count(&self) -> Result<u32> {
+        let mut last = 0;
+        for bucket in 0..self.bucket_count() {
+            last = last.max(read_u32(self.buckets, bucket)?);
+        }
+        if last < self.symbol_offset {
+            return Ok(self.symbol_offset)
+        }
+        loop {
+            let hash = read_u32(self.chains, last - self.symbol_offset)?;
+            last = last.checked_add(1).ok_or(Error::IntegerOverflow)?;
+            if hash & 1 != 0 {
+                return Ok(last)
+            }
+        }
+    }
+    /// Find the symbol with the given name in `symbols`, the table this hash table was built for.
+    pub fn find<C: Class>(&self, symbols: &SymbolTable<'a, C>, name: &str) -> Result<Option<(u32, &'a C::Symbol)>> {
+        if self.buckets.is_empty() {
+            return Ok(None)
+        }
+        let hash = Self::hash(name.as_bytes());
+        // Both bits selected by the hash must be set in the bloom filter word for the name to be present
+        let bits = self.bloom_word_size as u32 * 8;
+        let word = (hash / bits) as usize % (self.bloom.len() / self.bloom_word_size) * self.bloom_word_size;
+        let mut bytes = [0; 8];
+        bytes[..self.bloom_word_size].copy_from_slice(&self.bloom[word..][..self.bloom_word_size]);
+        let word = u64::from_le_bytes(bytes);
+        let mask = 1 << (hash % bits) | 1 << (hash.wrapping_shr(self.bloom_shift) % bits);
+        if word & mask != mask {
+            return Ok(None)
+        }
+        let mut index = read_u32(self.buckets, hash % self.bucket_count())?;
+        if index < self.symbol_offset {
+            return Ok(None)
+        }
+        loop {
+            let chain = read_u32(self.chains, index - self.symbol_offset)?;
+            if chain | 1 == hash | 1 {
+                let symbol = symbols.get(index)?;
+                if has_name(symbols, symbol, name)? {
+                    return Ok(Some((index, symbol)))
+                }
+            }
+            if chain & 1 != 0 {
+                return Ok(None)
+            }
+            index = index.checked_add(1).ok_or(Error::IntegerOverflow)?;
+        }
+    }
+}
+impl<'a> fmt::Debug for GnuHashTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GnuHashTable")
+            .field("symbol_offset", &self.symbol_offset)
+            .field("bloom_shift", &self.bloom_shift)
+            .field("bloom", &[..])
+            .field("buckets", &self.bucket_count())
+            .finish()
+    }
+}
+
+/// Get `count` words of `data` starting at word `start`.
+fn words(data: &[u8], start: u32, count: u32) -> Result<&[u8]> {
+    let start = (start as usize).checked_mul(4).ok_or(Error::IntegerOverflow)?;
+    let size = (count as usize).checked_mul(4).ok_or(Error::IntegerOverflow)?;
+    data.get(start..).and_then(|data| data.get(..size)).ok_or(Error::UnexpectedEoF)
+}
+
+/// Returns true if `symbol` is named `name`.
+fn has_name<'a, C: Class>(symbols: &SymbolTable<'a, C>, symbol: &C::Symbol, name: &str) -> Result<bool> {
+    Ok(symbol.name() != 0 && symbols.strings.get_bytes(symbol.name())? == name.as_bytes())
+}
@@ -0,0 +1,379 @@
+use core::fmt;
+use crate::{Class, Elf, ElfHeader, Error, FloatAbi, Result, RiscVFlags};
+
+impl<'a, C: Class> Elf<'a, C> {
+    /// Get the ISA the file was built for from the `Tag_RISCV_arch` build attribute, if it has one.
+    pub fn isa(&self) -> Result<Option<IsaString<'a>>> {
+        match self.riscv_attributes()? {
+            Some(attributes) => attributes.arch()?.map(IsaString::new).transpose(),
+            None => Ok(None)
+        }
+    }
+    /// Check that a hart implementing `hart` can run this file.
+    ///
+    /// The extensions needed by the float ABI and `EF_RISCV_RVC` in `e_flags` are checked first, then those listed by
+    /// the `Tag_RISCV_arch` attribute if the file has one.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// # let mut data = [0u32; 256];
+    /// # let elf = include_bytes!("../examples/attributes.elf");
+    /// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+    /// let elf = Elf::new(&data)?;
+    /// elf.check_isa(&IsaString::new("rv32gc")?)?;
+    /// match elf.check_isa(&IsaString::new("rv32imac_zicsr")?) {
+    ///     Err(Error::MissingExtension(name)) => assert_eq!(name.as_str(), "d"),
+    ///     result => panic!("{result:?}")
+    /// }
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn check_isa(&self, hart: &IsaString) -> Result<()> {
+        hart.check_flags(self.header.riscv_flags())?;
+        match self.isa()? {
+            Some(isa) => isa.check_compatible(hart),
+            None => Ok(())
+        }
+    }
+}
+
+/// A parsed RISC-V ISA string such as `rv32imac_zicsr` or `rv32i2p1_m2p0_c2p0`.
+///
+/// The string is made up of the base ISA, single-letter extensions and then multi-letter `Z*`, `S*` and `X*`
+/// extensions separated by underscores. Each extension may be followed by a version such as `2p1`. Case is ignored.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// let isa = IsaString::new("rv32i2p1_m2p0_c_zicsr2p0")?;
+/// assert_eq!(isa.xlen(), 32);
+/// assert!(isa.requires(Extension::C));
+/// assert!(!isa.requires(Extension::F));
+/// assert_eq!(isa.get("zicsr").unwrap().version, Some(Version { major: 2, minor: 0 }));
+/// // Extensions implied by others are provided too
+/// assert!(isa.provides(Extension::Zca));
+/// assert!(isa.is_compatible_with(&IsaString::new("rv32imac_zicsr")?));
+/// assert!(!IsaString::new("rv32gc")?.is_compatible_with(&isa));
+/// # Ok(()) })().unwrap()
+/// ```
+#[derive(Clone, Copy)]
+pub struct IsaString<'a> {
+    string: &'a str,
+    xlen: u8
+}
+impl<'a> IsaString<'a> {
+    /// Parse an ISA string, which must begin with `rv32` or `rv64` followed by the base `i`, `e` or `g`.
+    pub fn new(string: &'a str) -> Result<Self> {
+        let prefix = string.get(..4).ok_or(Error::InvalidIsaString)?;
+        let xlen = if prefix.eq_ignore_ascii_case("rv32") {
+            32
+        } else if prefix.eq_ignore_ascii_case("rv64") {
+            64
+        } else {
+            return Err(Error::InvalidIsaString)
+        };
+        let isa = Self { string, xlen };
+        let mut rest = &string[4..];
+        let mut first = true;
+        while let Some((extension, next)) = next_extension(rest)? {
+            if first && !["i", "e", "g"].iter().any(|base| extension.is(base)) {
+                return Err(Error::InvalidIsaString)
+            }
+            first = false;
+            rest = next;
+        }
+        if first {
+            return Err(Error::InvalidIsaString)
+        }
+        Ok(isa)
+    }
+    /// The string this was parsed from.
+    pub fn as_str(&self) -> &'a str {
+        self.string
+    }
+    /// The width of the integer registers, either 32 or 64.
+    pub fn xlen(&self) -> u32 {
+        self.xlen.into()
+    }
+    /// The base ISA, which is `i`, `e` or `g`.
+    pub fn base(&self) -> Extension<'a> {
+        self.extensions().next().unwrap_or(Extension::I)
+    }
+    /// Get an iterator over the extensions named by the string, starting with the base ISA.
+    pub fn extensions(&self) -> Extensions<'a> {
+        Extensions { rest: &self.string[4..] }
+    }
+    /// Find the extension named `name` if the string lists it explicitly.
+    pub fn get(&self, name: &str) -> Option<Extension<'a>> {
+        self.extensions().find(|extension| extension.is(name))
+    }
+    /// Returns true if the string explicitly lists `extension`.
+    ///
+    /// If `extension` has a version, the listed version must not be older.
+    pub fn requires(&self, extension: Extension) -> bool {
+        self.get(extension.name).is_some_and(|listed| listed.satisfies(&extension))
+    }
+    /// Returns true if `extension` is listed or implied by a listed extension, such as `zicsr` by `f`.
+    ///
+    /// If `extension` has a version, a listed version must not be older. Implied extensions match any version.
+    pub fn provides(&self, extension: Extension) -> bool {
+        if let Some(listed) = self.get(extension.name) {
+            return listed.satisfies(&extension)
+        }
+        let has = |name| self.provides(Extension::new(name));
+        match ExtensionName::lowercase(extension.name) {
+            Some(name) => match name.as_str() {
+                "e" => has("i"),
+                "i" | "m" | "a" | "f" | "d" | "zicsr" | "zifencei" if self.get("g").is_some() => true,
+                "zmmul" => has("m"),
+                "zaamo" | "zalrsc" => has("a"),
+                "zicsr" => has("f"),
+                "f" => has("d"),
+                "zca" => has("c"),
+                "zcf" => self.xlen == 32 && has("c") && has("f"),
+                "zcd" => has("c") && has("d"),
+                _ => false
+            },
+            None => false
+        }
+    }
+    /// Returns true if a hart implementing `hart` can run code built for this ISA.
+    pub fn is_compatible_with(&self, hart: &IsaString) -> bool {
+        self.check_compatible(hart).is_ok()
+    }
+    /// Check that a hart implementing `hart` can run code built for this ISA, naming the first thing it lacks.
+    pub fn check_compatible(&self, hart: &IsaString) -> Result<()> {
+        if self.xlen != hart.xlen {
+            return Err(Error::IncompatibleXlen { required: self.xlen(), available: hart.xlen() })
+        }
+        for extension in self.extensions() {
+            let missing = if extension.is("g") {
+                ["i", "m", "a", "f", "d", "zicsr", "zifencei"].into_iter()
+                    .find(|name| !hart.provides(Extension::new(name)))
+            } else if hart.provides(extension) {
+                None
+            } else {
+                Some(extension.name)
+            };
+            if let Some(name) = missing {
+                return Err(Error::MissingExtension(ExtensionName::new(name)))
+            }
+        }
+        Ok(())
+    }
+    /// Check that a hart implementing this ISA has the extensions needed by code built with `flags`.
+    pub fn check_flags(&self, flags: RiscVFlags) -> Result<()> {
+        let float = match flags.float_abi() {
+            FloatAbi::Single => Some(Extension::F),
+            FloatAbi::Double => Some(Extension::D),
+            FloatAbi::Quad => Some(Extension::Q),
+            _ => None
+        };
+        let compressed = flags.all(RiscVFlags::Rvc).then_some(Extension::C);
+        for extension in [float, compressed].into_iter().flatten() {
+            if !self.provides(extension) {
+                return Err(Error::MissingExtension(ExtensionName::new(extension.name)))
+            }
+        }
+        Ok(())
+    }
+}
+impl<'a> fmt::Debug for IsaString<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IsaString")
+            .field(&self.string)
+            .finish()
+    }
+}
+impl<'a> fmt::Display for IsaString<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.string)
+    }
+}
+
+/// An iterator over the extensions of an `IsaString`.
+#[derive(Debug, Clone)]
+pub struct Extensions<'a> {
+    rest: &'a str
+}
+impl<'a> Iterator for Extensions<'a> {
+    type Item = Extension<'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        // The string was validated when the `IsaString` was created
+        let (extension, rest) = next_extension(self.rest).ok()??;
+        self.rest = rest;
+        Some(extension)
+    }
+}
+
+/// An ISA extension and the version it was listed with, if any.
+#[derive(Debug, Clone, Copy)]
+pub struct Extension<'a> {
+    pub name: &'a str,
+    pub version: Option<Version>
+}
+#[allow(non_upper_case_globals)]
+impl<'a> Extension<'a> {
+    pub const I: Self = Self::new("i");
+    pub const E: Self = Self::new("e");
+    pub const G: Self = Self::new("g");
+    pub const M: Self = Self::new("m");
+    pub const A: Self = Self::new("a");
+    pub const F: Self = Self::new("f");
+    pub const D: Self = Self::new("d");
+    pub const Q: Self = Self::new("q");
+    pub const C: Self = Self::new("c");
+    pub const B: Self = Self::new("b");
+    pub const V: Self = Self::new("v");
+    pub const H: Self = Self::new("h");
+    pub const Zicsr: Self = Self::new("zicsr");
+    pub const Zifencei: Self = Self::new("zifencei");
+    pub const Zmmul: Self = Self::new("zmmul");
+    pub const Zaamo: Self = Self::new("zaamo");
+    pub const Zalrsc: Self = Self::new("zalrsc");
+    pub const Zca: Self = Self::new("zca");
+    pub const Zcf: Self = Self::new("zcf");
+    pub const Zcd: Self = Self::new("zcd");
+    pub const Zba: Self = Self::new("zba");
+    pub const Zbb: Self = Self::new("zbb");
+    pub const Zbs: Self = Self::new("zbs");
+
+    /// An extension with any version.
+    pub const fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            version: None
+        }
+    }
+    /// Returns true if this extension is called `name`, ignoring case.
+    pub fn is(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+    /// Returns true if this extension can stand in for `required`.
+    fn satisfies(&self, required: &Extension) -> bool {
+        match (self.version, required.version) {
+            (Some(version), Some(required)) => version >= required,
+            _ => true
+        }
+    }
+}
+impl<'a> PartialEq for Extension<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.is(other.name) && self.version == other.version
+    }
+}
+impl<'a> Eq for Extension<'a> {}
+
+/// The version of an extension, written as `<major>p<minor>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32
+}
+
+/// The name of an extension, held inline so that it can be reported in an `Error`.
+///
+/// Names longer than the buffer are truncated.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ExtensionName {
+    bytes: [u8; 24],
+    len: u8
+}
+impl ExtensionName {
+    pub fn new(name: &str) -> Self {
+        let mut bytes = [0; 24];
+        // Truncate on a character boundary so the name stays valid UTF-8
+        let mut len = name.len().min(bytes.len());
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+        bytes[..len].copy_from_slice(&name.as_bytes()[..len]);
+        for byte in &mut bytes[..len] {
+            byte.make_ascii_lowercase();
+        }
+        Self {
+            bytes,
+            len: len as u8
+        }
+    }
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
+    }
+    /// A lower-case copy of `name`, or `None` if it does not fit.
+    fn lowercase(name: &str) -> Option<Self> {
+        if name.len() > 24 {
+            None
+        } else {
+            Some(Self::new(name))
+        }
+    }
+}
+impl fmt::Debug for ExtensionName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// Split the next extension off the start of `rest`, skipping any leading underscore.
+fn next_extension(rest: &str) -> Result<Option<(Extension<'_>, &str)>> {
+    let rest = rest.strip_prefix('_').unwrap_or(rest);
+    let first = match rest.bytes().next() {
+        Some(first) => first.to_ascii_lowercase(),
+        None => return Ok(None)
+    };
+    if !first.is_ascii_alphabetic() {
+        return Err(Error::InvalidIsaString)
+    }
+    if matches!(first, b'z' | b's' | b'x') {
+        // Multi-letter extensions run to the next underscore and may end in a version
+        let end = rest.find('_').unwrap_or(rest.len());
+        let (name, version) = split_version(&rest[..end])?;
+        if name.len() < 2 || !name.bytes().all(|byte| byte.is_ascii_alphanumeric()) {
+            return Err(Error::InvalidIsaString)
+        }
+        Ok(Some((Extension { name, version }, &rest[end..])))
+    } else {
+        let (version, next) = parse_version(&rest[1..])?;
+        Ok(Some((Extension { name: &rest[..1], version }, next)))
+    }
+}
+/// Parse a version such as `2` or `2p1` from the start of `rest`, returning it with the remaining string.
+fn parse_version(rest: &str) -> Result<(Option<Version>, &str)> {
+    let digits = |s: &str| s.bytes().take_while(u8::is_ascii_digit).count();
+    let end = digits(rest);
+    if end == 0 {
+        return Ok((None, rest))
+    }
+    let major = rest[..end].parse().map_err(|_| Error::InvalidIsaString)?;
+    let rest = &rest[end..];
+    let minor_end = match rest.strip_prefix(['p', 'P']) {
+        Some(minor) => digits(minor),
+        None => 0
+    };
+    if minor_end == 0 {
+        return Ok((Some(Version { major, minor: 0 }), rest))
+    }
+    let minor = rest[1..1 + minor_end].parse().map_err(|_| Error::InvalidIsaString)?;
+    Ok((Some(Version { major, minor }), &rest[1 + minor_end..]))
+}
+/// Split a trailing version such as `2p0` off the name of a multi-letter extension.
+fn split_version(extension: &str) -> Result<(&str, Option<Version>)> {
+    let trailing = |s: &str| s.len() - s.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let digits = trailing(extension);
+    if digits == 0 {
+        return Ok((extension, None))
+    }
+    let mut start = extension.len() - digits;
+    let before = &extension[..start];
+    if let Some(major) = before.strip_suffix(['p', 'P']) {
+        let major_digits = trailing(major);
+        if major_digits > 0 {
+            start = major.len() - major_digits;
+        }
+    }
+    let (version, rest) = parse_version(&extension[start..])?;
+    if !rest.is_empty() {
+        return Err(Error::InvalidIsaString)
+    }
+    Ok((&extension[..start], version))
+}
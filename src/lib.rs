@@ -1,6 +1,6 @@
 #![no_std]
 
-use core::{mem::{size_of, align_of}, fmt};
+use core::{mem::size_of, fmt};
 
 macro_rules! c_enum {
     (
@@ -10,7 +10,7 @@ macro_rules! c_enum {
     ) => {
         #[derive(::core::clone::Clone, ::core::marker::Copy, ::core::cmp::PartialEq, ::core::cmp::Eq)]
         #[repr(transparent)]
-        $vis struct $name($ty);
+        $vis struct $name(pub(crate) $ty);
         impl $name {
             $(
                 #[allow(non_upper_case_globals)]
@@ -26,6 +26,7 @@ macro_rules! c_enum {
         impl ::core::fmt::Debug for $name {
             fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
                 #[derive(Debug)]
+                #[allow(dead_code)]
                 struct Unknown($ty);
                 match *self {
                     $(Self::$item => f.write_str(stringify!($item)),)*
@@ -47,6 +48,15 @@ macro_rules! c_enum {
                 value.0
             }
         }
+        impl $crate::Endian for $name {
+            type Bytes = <$ty as $crate::Endian>::Bytes;
+            fn from_le_bytes(bytes: Self::Bytes) -> Self {
+                Self(<$ty as $crate::Endian>::from_le_bytes(bytes))
+            }
+            fn to_le_bytes(self) -> Self::Bytes {
+                <$ty as $crate::Endian>::to_le_bytes(self.0)
+            }
+        }
     }
 }
 macro_rules! c_flags {
@@ -57,7 +67,7 @@ macro_rules! c_flags {
     ) => {
         #[derive(::core::clone::Clone, ::core::marker::Copy, ::core::cmp::PartialEq, ::core::cmp::Eq)]
         #[repr(transparent)]
-        $vis struct $name($ty);
+        $vis struct $name(pub(crate) $ty);
         impl $name {
             #[allow(non_upper_case_globals)]
             $vis const None: Self = Self(0);
@@ -118,6 +128,7 @@ macro_rules! c_flags {
         impl ::core::fmt::Debug for $name {
             fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
                 #[derive(Debug)]
+                #[allow(dead_code)]
                 struct Unknown($ty);
                 $(
                     #[derive(Debug)]
@@ -146,8 +157,61 @@ macro_rules! c_flags {
                 value.0
             }
         }
+        impl $crate::Endian for $name {
+            type Bytes = <$ty as $crate::Endian>::Bytes;
+            fn from_le_bytes(bytes: Self::Bytes) -> Self {
+                Self(<$ty as $crate::Endian>::from_le_bytes(bytes))
+            }
+            fn to_le_bytes(self) -> Self::Bytes {
+                <$ty as $crate::Endian>::to_le_bytes(self.0)
+            }
+        }
     }
 }
+#[macro_use]
+mod view;
+pub use view::*;
+#[macro_use]
+mod class;
+pub use class::*;
+mod bytes;
+mod symbol;
+pub use symbol::*;
+mod hash;
+pub use hash::*;
+mod symbolizer;
+pub use symbolizer::*;
+mod relocation;
+pub use relocation::*;
+mod memory;
+pub use memory::*;
+mod module;
+pub use module::*;
+mod loader;
+pub use loader::*;
+mod dynamic;
+pub use dynamic::*;
+mod attributes;
+pub use attributes::*;
+mod isa;
+pub use isa::*;
+mod flags;
+pub use flags::*;
+mod note;
+pub use note::*;
+mod coredump;
+pub use coredump::*;
+mod write;
+pub use write::*;
+mod builder;
+pub use builder::*;
+mod editor;
+pub use editor::*;
+mod export;
+pub use export::*;
+mod uf2;
+pub use uf2::*;
+
 pub type Result<T> = core::result::Result<T, Error>;
 #[derive(Debug)]
 pub enum Error {
@@ -169,28 +233,89 @@ pub enum Error {
     WrongProgramFlags { expected: ProgramFlags, actual: ProgramFlags },
     WrongSectionType { expected: SectionType, actual: SectionType },
     WrongSectionFlags { expected: SectionFlags, actual: SectionFlags },
+    UnsupportedSymbolBinding(SymbolBinding),
+    UnsupportedSymbolType(SymbolType),
+    UnsupportedSymbolVisibility(SymbolVisibility),
+    UnsupportedRelocationType(RelocationType),
+    UnsupportedSymbolSection(SymbolSection),
+    InvalidEntrySize,
+    AddressOutOfRange,
+    UnresolvedSymbol(u32),
+    RelocationOutOfRange { ty: RelocationType, address: u32 },
+    MisalignedRelocation { ty: RelocationType, address: u32 },
+    MissingPcrelHi20 { address: u32 },
+    MissingGot { ty: RelocationType, address: u32 },
+    InvalidSegmentSize,
+    MisalignedSegment { address: u32 },
+    SegmentOutOfBounds { address: u32 },
+    SegmentOverlap { address: u32 },
+    UnsupportedDynamicTag(DynamicTag),
+    UnsupportedDynamicFlags(DynamicFlags),
+    MissingDynamicTag(DynamicTag),
+    WrongDynamicTag { expected: DynamicTag, actual: DynamicTag },
+    UnsupportedAttributeScope(AttributeScope),
+    UnsupportedAttributeTag(AttributeTag),
+    InvalidIsaString,
+    IncompatibleXlen { required: u32, available: u32 },
+    MissingExtension(ExtensionName),
+    UnsupportedRiscVFlags(RiscVFlags),
+    UnsupportedFloatAbi(FloatAbi),
+    WrongFloatAbi { expected: FloatAbi, actual: FloatAbi },
+    WrongRiscVFlags { expected: RiscVFlags, actual: RiscVFlags },
+    MissingSectionNames,
+    MissingSymbolTable,
+    WrongClass { expected: u8, actual: u8 },
+    UnsupportedNoteType(GnuNoteType),
+    UnsupportedAbiOs(AbiOs),
+    UnsupportedGnuPropertyType(GnuPropertyType),
+    UnsupportedCoreNoteType(CoreNoteType),
+    BufferTooSmall,
+    LocalSymbolAfterGlobal,
+    SectionInUse(u32),
+    UnsupportedUf2Flags(Uf2Flags),
+    UnsupportedUf2Family(Uf2Family),
     UnterminatedString,
     NotUtf8(core::str::Utf8Error)
 }
 
 #[derive(Debug, Clone, Copy)]
 #[repr(transparent)]
-pub struct Address(u32);
-impl Address {
+pub struct Address<W = u32>(W);
+impl<W: Word> Address<W> {
     pub fn as_usize(self) -> Result<usize> {
-        self.0.try_into().map_err(|_| Error::IntegerOverflow)
+        self.0.as_usize()
+    }
+}
+impl<W: Word> Endian for Address<W> {
+    type Bytes = W::Bytes;
+    fn from_le_bytes(bytes: Self::Bytes) -> Self {
+        Self(W::from_le_bytes(bytes))
+    }
+    fn to_le_bytes(self) -> Self::Bytes {
+        self.0.to_le_bytes()
     }
 }
 #[derive(Debug, Clone, Copy)]
 #[repr(transparent)]
-pub struct Offset(u32);
-impl Offset {
+pub struct Offset<W = u32>(W);
+impl<W: Word> Offset<W> {
     pub fn as_usize(self) -> Result<usize> {
-        self.0.try_into().map_err(|_| Error::IntegerOverflow)
+        self.0.as_usize()
+    }
+}
+impl<W: Word> Endian for Offset<W> {
+    type Bytes = W::Bytes;
+    fn from_le_bytes(bytes: Self::Bytes) -> Self {
+        Self(W::from_le_bytes(bytes))
+    }
+    fn to_le_bytes(self) -> Self::Bytes {
+        self.0.to_le_bytes()
     }
 }
 
-/// A 32bit little-endian RISC-V ELF file.
+/// A little-endian RISC-V ELF file of class `C`, which defaults to `Elf32` for RV32 targets.
+///
+/// Use `Elf<Elf64>` to read RV64 files. The loaders and the dynamic section only support `Elf32`.
 /// 
 /// ```
 /// use elf_riscv32::*;
@@ -212,53 +337,207 @@ impl Offset {
 /// }
 /// # Ok(()) })().unwrap()
 /// ```
-pub struct Elf<'a> {
+pub struct Elf<'a, C: Class = Elf32> {
     data: &'a [u8],
-    pub header: &'a Header,
-    pub section_names: StringTable<'a>
+    pub header: &'a C::Header,
+    /// The section header string table, or `None` if the file has no section headers or `e_shstrndx` is
+    /// `SHN_UNDEF`.
+    pub section_names: Option<StringTable<'a>>,
+    program_count: u32,
+    section_count: u32
 }
+impl<'a, C: Class> Clone for Elf<'a, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'a, C: Class> Copy for Elf<'a, C> {}
 impl<'a> Elf<'a> {
+    /// Parse the file header and find the section header string table.
+    ///
+    /// Section headers are optional, so images stripped down to their program headers, such as those produced by
+    /// `llvm-objcopy --strip-sections`, can still be loaded.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// # let mut data = [0u32; 64];
+    /// # let elf = include_bytes!("../examples/stripped.elf");
+    /// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+    /// let elf = Elf::new(&data)?;
+    /// assert!(elf.section_names.is_none());
+    /// assert_eq!(elf.sections()?.count(), 0);
+    /// let mut buffer = [0u8; 0x2000];
+    /// let loaded = Loader::new(&elf).load(&mut Region::new(0x10000, &mut buffer))?;
+    /// assert_eq!(loaded.entry, elf.header.entry.get().as_usize()? as u32);
+    /// # Ok(()) })().unwrap()
+    /// ```
     pub fn new(elf: &'a [u32]) -> Result<Self> {
-        assert_eq!(align_of::<u32>(), align_of::<Header>());
-        let data = unsafe { core::slice::from_raw_parts(elf.as_ptr() as *const u8, elf.len() * size_of::<u32>()) };
-        let header = unsafe { Header::new_assume_aligned(data)? };
-        let section_name_table = header.section_header(data, header.section_name_table)?;
-        let section_names = StringTable(section_name_table.data(data)?);
+        Self::from_bytes(words_as_bytes(elf))
+    }
+    /// Like `Elf::new` but takes a byte slice with any alignment.
+    ///
+    /// The headers are still borrowed rather than copied; fields are read with unaligned loads.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// let mut buffer = [0u8; 1024];
+    /// let file = include_bytes!("../examples/test.elf");
+    /// buffer[1..][..file.len()].copy_from_slice(file);
+    /// let elf = Elf::from_bytes(&buffer[1..])?;
+    /// assert_eq!(elf.header.ty, FileType::Executable);
+    /// let text = elf.sections()?.flatten().find(|section| matches!(elf.section_name(section), Ok(".text")));
+    /// assert!(text.is_some());
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self> {
+        Self::parse(data)
+    }
+}
+impl<'a, C: Class> Elf<'a, C> {
+    /// Parse a file of class `C` from a byte slice with any alignment.
+    ///
+    /// `Elf::new` and `Elf::from_bytes` parse `Elf32` files without naming the class; RV64 files are parsed with
+    /// `Elf::<Elf64>::parse`. A file of the other class is rejected with `Error::WrongClass`.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// let file = include_bytes!("../examples/test64.elf");
+    /// let elf = Elf::<Elf64>::parse(file)?;
+    /// assert_eq!(elf.header.ty, FileType::Executable);
+    /// assert!(elf.header.entry.get().as_usize()? > 0xFFFF_FFFF);
+    ///
+    /// let symbols = elf.symbol_table()?.unwrap();
+    /// let (_, start) = symbols.find("_start")?.unwrap();
+    /// assert_eq!(start.value().as_usize()?, elf.header.entry.get().as_usize()?);
+    /// assert_eq!(start.binding(), SymbolBinding::Global);
+    ///
+    /// let (_, message) = symbols.find("message")?.unwrap();
+    /// let data = elf.segment_data(message.value().as_usize()? as u64, message.size())?;
+    /// assert_eq!(data, b"hello from rv64\0");
+    ///
+    /// let rela = elf.find_section(SectionType::Rela)?.unwrap();
+    /// let table = RelaTable64::new(&elf, rela)?;
+    /// assert_eq!(elf.section_name(&table.target.unwrap())?, ".text");
+    /// let first = table.relocations()?.next().unwrap()?;
+    /// assert_eq!(first.ty(), RelocationType::PcrelHi20);
+    /// assert_eq!(table.symbols.unwrap().name(table.symbol(first.symbol())?)?, "message");
+    ///
+    /// assert!(matches!(Elf::from_bytes(file), Err(Error::WrongClass { expected: 1, actual: 2 })));
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let header = C::Header::from_bytes(data)?;
+        let program_count = header.program_count(data)?;
+        let section_count = header.section_count(data)?;
+        // Extended numbering lets a tiny file claim billions of headers, so the tables must be present in full
+        check_table(data, header.ph_offset().0.into(), program_count, header.ph_entry_size())?;
+        check_table(data, header.sh_offset().0.into(), section_count, header.sh_entry_size())?;
+        let section_name_index = header.section_name_index(data)?;
+        let section_names = if section_count == 0 || section_name_index == 0 {
+            None
+        } else {
+            let section_name_table = header.section_header(data, section_name_index)?;
+            Some(StringTable(section_name_table.data(data)?))
+        };
         Ok(Self {
             data,
             header,
-            section_names
+            section_names,
+            program_count,
+            section_count
         })
     }
     pub fn check_type(&self, ty: FileType) -> Result<()> {
-        if self.header.ty != ty {
-            Err(Error::WrongFileType { expected: ty, actual: self.header.ty })
+        if self.header.ty() != ty {
+            Err(Error::WrongFileType { expected: ty, actual: self.header.ty() })
         } else {
             Ok(())
         }
     }
-    pub fn program(&self, index: u16) -> Result<Program<'a>> {
+    /// The number of program headers, following the extended numbering rules of `ElfHeader::program_count`.
+    pub fn program_count(&self) -> u32 {
+        self.program_count
+    }
+    /// The number of section headers, following the extended numbering rules of `ElfHeader::section_count`.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// # let mut data = [0u32; 256];
+    /// # let elf = include_bytes!("../examples/extended.elf");
+    /// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+    /// let elf = Elf::new(&data)?;
+    /// assert_eq!((elf.header.sh_count.get(), elf.header.ph_count.get()), (0, 0xFFFF));
+    /// assert_eq!((elf.section_count(), elf.program_count()), (8, 4));
+    /// assert_eq!(elf.section_name(&elf.section(7)?)?, ".symtab_shndx");
+    ///
+    /// let symbols = elf.symbol_table()?.unwrap();
+    /// let (index, symbol) = symbols.find("_start")?.unwrap();
+    /// assert_eq!(symbol.section(), SymbolSection::Extended);
+    /// assert_eq!(symbols.section(index)?, SymbolSection::Index(2));
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn section_count(&self) -> u32 {
+        self.section_count
+    }
+    pub fn program(&self, index: u32) -> Result<Program<'a, C>> {
         let header = self.header.program_header(self.data, index)?;
         header.data(self.data).map(|data| Program::new(header, data))
     }
     /// Get an iterator over programs.
-    pub fn programs(&'a self) -> Result<TableIter<Program<'a>>> {
-        TableIter::new(self.data, self.header.ph_offset, self.header.ph_count, self.header.ph_entry_size)
+    pub fn programs(&self) -> Result<TableIter<'a, Program<'a, C>>> {
+        TableIter::new(self.data, self.header.ph_offset(), self.program_count, self.header.ph_entry_size())
     }
     /// Get the section name string given an offset into the section header string table.
-    pub fn section_name(&'a self, section: &Section<'a>) -> Result<&'a str> {
-        self.section_names.get_str(section.header.name)
+    ///
+    /// Returns `Error::MissingSectionNames` if the file has no section header string table.
+    pub fn section_name(&self, section: &Section<'a, C>) -> Result<&'a str> {
+        self.section_names.ok_or(Error::MissingSectionNames)?.get_str(section.header.name())
     }
-    pub fn section(&self, index: u16) -> Result<Section<'a>> {
+    pub fn section(&self, index: u32) -> Result<Section<'a, C>> {
         let header = self.header.section_header(self.data, index)?;
         header.data(self.data).map(|data| Section::new(header, data))
     }
     /// Get an iterator over sections.
-    pub fn sections(&'a self) -> Result<TableIter<Section<'a>>> {
-        TableIter::new(self.data, self.header.sh_offset, self.header.sh_count, self.header.sh_entry_size)
+    pub fn sections(&self) -> Result<TableIter<'a, Section<'a, C>>> {
+        TableIter::new(self.data, self.header.sh_offset(), self.section_count, self.header.sh_entry_size())
+    }
+    /// Get the file data backing `size` bytes at the virtual `address`, found through the `PT_LOAD` segments.
+    pub fn segment_data(&self, address: C::Word, size: C::Word) -> Result<&'a [u8]> {
+        let (address, size): (u64, u64) = (address.into(), size.into());
+        for program in self.programs()? {
+            let program = program?;
+            if program.header.ty() != ProgramType::Load {
+                continue
+            }
+            let start: u64 = program.header.virt_addr().0.into();
+            if address < start {
+                continue
+            }
+            let offset = address - start;
+            if offset.checked_add(size).is_some_and(|end| end <= program.header.file_size().into()) {
+                let start = offset.try_into().map_err(|_| Error::IntegerOverflow)?;
+                let size: usize = size.try_into().map_err(|_| Error::IntegerOverflow)?;
+                return program.data.get(start..).and_then(|data| data.get(..size)).ok_or(Error::UnexpectedEoF)
+            }
+        }
+        Err(Error::AddressOutOfRange)
+    }
+    /// Find the first section of the given type.
+    pub fn find_section(&self, ty: SectionType) -> Result<Option<Section<'a, C>>> {
+        for section in self.sections()? {
+            let section = section?;
+            if section.header.ty() == ty {
+                return Ok(Some(section))
+            }
+        }
+        Ok(None)
     }
 }
-impl<'a> fmt::Debug for Elf<'a> {
+impl<'a, C: Class> fmt::Debug for Elf<'a, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Elf")
             .field("data", &[..])
@@ -268,98 +547,100 @@ impl<'a> fmt::Debug for Elf<'a> {
     }
 }
 
-#[derive(Debug)]
-#[repr(C)]
-pub struct Header {
-    pub ident: [u8; 16],
-    pub ty: FileType,
-    pub machine: Machine,
-    pub version: u32,
-    pub entry: Address,
-    pub ph_offset: Offset,
-    pub sh_offset: Offset,
-    pub flags: u32,
-    pub header_size: u16,
-    pub ph_entry_size: u16,
-    pub ph_count: u16,
-    pub sh_entry_size: u16,
-    pub sh_count: u16,
-    pub section_name_table: u16
+view!{
+    #[derive(Debug)]
+    pub struct Header {
+        pub ident: [u8; 16],
+        pub ty: Le<FileType>,
+        pub machine: Le<Machine>,
+        pub version: Le<u32>,
+        pub entry: Le<Address>,
+        pub ph_offset: Le<Offset>,
+        pub sh_offset: Le<Offset>,
+        pub flags: Le<u32>,
+        pub header_size: Le<u16>,
+        pub ph_entry_size: Le<u16>,
+        pub ph_count: Le<u16>,
+        pub sh_entry_size: Le<u16>,
+        pub sh_count: Le<u16>,
+        pub section_name_table: Le<u16>
+    }
 }
 impl Header {
     pub fn new(elf: &[u32]) -> Result<&Self> {
-        assert_eq!(align_of::<u32>(), align_of::<Self>());
-        unsafe {
-            let len = elf.len() * size_of::<u32>();
-            Self::new_assume_aligned(core::slice::from_raw_parts(elf.as_ptr() as *const u8, len))
-        }
+        Self::from_bytes(words_as_bytes(elf))
     }
-    /// Like `Header::new` but takes a byte slice and checks the alignment.
+    /// Like `Header::new` but takes a byte slice.
+    #[deprecated(note = "any alignment is accepted, use `Header::from_bytes`")]
     pub fn new_aligned(elf: &[u8]) -> Result<&Self> {
-        assert_eq!(align_of::<u32>(), align_of::<Self>());
-        if elf.as_ptr() as usize & 0b11 != 0 {
-            Err(Error::Unaligned)
-        } else {
-            unsafe { Self::new_assume_aligned(elf) }
-        }
+        Self::from_bytes(elf)
     }
     /// Coerce a byte slice into an ELF header.
-    /// 
+    ///
     /// # Safety
-    /// It is undefined behaviour for `elf` to have a smaller alignment than `Header`.
+    /// Always safe to call, as `Header` no longer has any alignment requirement.
+    #[deprecated(note = "any alignment is accepted, use `Header::from_bytes`")]
     pub unsafe fn new_assume_aligned(elf: &[u8]) -> Result<&Self> {
-        if elf.len() < size_of::<Self>() {
-            return Err(Error::UnexpectedEoF)
-        }
-        let elf = &*(elf.as_ptr() as *const Header);
-        if &elf.ident[..4] != b"\x7fELF" {
-            return Err(Error::InvalidMagic)
-        }
-        if elf.ident[4] != 1 || elf.ident[5] != 1 {
-            // Not riscv32-le
-            return Err(Error::InvalidFormat)
-        }
-        if elf.ident[6] != 1 || elf.version != 1 {
-            return Err(Error::InvalidVersion)
-        }
-        elf.machine.validate()?;
-        Ok(elf)
+        Self::from_bytes(elf)
     }
-    pub fn program_header<'a>(&'a self, elf: &'a [u8], index: u16) -> Result<&'a ProgramHeader> {
-        if index >= self.ph_count {
-            Err(Error::IndexOutOfRange)
-        } else {
-            let offset: usize = self.ph_offset.as_usize()? + (self.ph_entry_size as usize * index as usize);
-            ProgramHeader::new(&elf[offset..])
-        }
+}
+impl ElfHeader<Elf32> for Header {
+    fn ident(&self) -> &[u8; 16] {
+        &self.ident
     }
-    pub fn section_header<'a>(&'a self, elf: &'a [u8], index: u16) -> Result<&'a SectionHeader> {
-        if index >= self.sh_count {
-            Err(Error::IndexOutOfRange)
-        } else {
-            let offset: usize = self.sh_offset.as_usize()? + (self.sh_entry_size as usize * index as usize);
-            SectionHeader::new(&elf[offset..])
-        }
+    fields!(
+        ty: FileType, machine: Machine, version: u32, entry: Address, ph_offset: Offset, sh_offset: Offset, flags: u32,
+        header_size: u16, ph_entry_size: u16, ph_count: u16, sh_entry_size: u16, sh_count: u16, section_name_table: u16
+    );
+}
+view!{
+    /// The ELF64 file header.
+    #[derive(Debug)]
+    pub struct Header64 {
+        pub ident: [u8; 16],
+        pub ty: Le<FileType>,
+        pub machine: Le<Machine>,
+        pub version: Le<u32>,
+        pub entry: Le<Address<u64>>,
+        pub ph_offset: Le<Offset<u64>>,
+        pub sh_offset: Le<Offset<u64>>,
+        pub flags: Le<u32>,
+        pub header_size: Le<u16>,
+        pub ph_entry_size: Le<u16>,
+        pub ph_count: Le<u16>,
+        pub sh_entry_size: Le<u16>,
+        pub sh_count: Le<u16>,
+        pub section_name_table: Le<u16>
+    }
+}
+impl ElfHeader<Elf64> for Header64 {
+    fn ident(&self) -> &[u8; 16] {
+        &self.ident
     }
+    fields!(
+        ty: FileType, machine: Machine, version: u32, entry: Address<u64>, ph_offset: Offset<u64>,
+        sh_offset: Offset<u64>, flags: u32, header_size: u16, ph_entry_size: u16, ph_count: u16, sh_entry_size: u16,
+        sh_count: u16, section_name_table: u16
+    );
 }
 
 pub struct TableIter<'a, T: 'a + TableEntry<'a>> {
     elf: &'a [u8],
-    offset: Offset,
-    count: u16,
+    offset: u64,
+    count: u32,
     size: u16,
-    index: u16,
+    index: u32,
     _marker: core::marker::PhantomData<T>
 }
 impl<'a, T: 'a + TableEntry<'a>> TableIter<'a, T> {
-    pub fn new(elf: &'a [u8], offset: Offset, count: u16, size: u16) -> Result<Self> {
-        assert_eq!(align_of::<u32>(), align_of::<T::Header>());
-        if elf.as_ptr() as usize & 0b11 != 0 || size & 0b11 != 0 {
-            Err(Error::Unaligned)
+    pub fn new<W: Word>(elf: &'a [u8], offset: Offset<W>, count: u32, size: u16) -> Result<Self> {
+        if count != 0 && (size as usize) < size_of::<T::Header>() {
+            // Overlapping entries would let a tiny file describe an enormous table
+            Err(Error::InvalidEntrySize)
         } else {
             Ok(Self {
                 elf,
-                offset,
+                offset: offset.0.into(),
                 count,
                 size,
                 index: 0,
@@ -374,30 +655,65 @@ impl<'a, T: 'a + TableEntry<'a>> Iterator for TableIter<'a, T> {
         if self.index == self.count {
             None
         } else {
-            let offset = match self.offset.as_usize() {
-                Ok(offset) => offset,
-                Err(e) => return Some(Err(e))
-            };
-            let offset = offset + self.index as usize * self.size as usize;
+            let index = self.index;
             self.index += 1;
-            self.elf.get(offset..).map(|header| T::new(self.elf, header))
+            let header = table_offset(self.offset, index, self.size).and_then(|offset| {
+                self.elf.get(offset..).filter(|header| header.len() >= self.size.into()).ok_or(Error::UnexpectedEoF)
+            });
+            match header {
+                Ok(header) => Some(T::new(self.elf, header)),
+                Err(error) => {
+                    // The following entries are further out of bounds
+                    self.index = self.count;
+                    Some(Err(error))
+                }
+            }
         }
     }
 }
 
+/// The file offset of entry `index` in a table of `size` byte entries starting at `offset`.
+fn table_offset(offset: u64, index: u32, size: u16) -> Result<usize> {
+    (index as usize).checked_mul(size as usize)
+        .and_then(|entry| entry.checked_add(offset.try_into().ok()?))
+        .ok_or(Error::IntegerOverflow)
+}
+
+/// Check that a table of `count` entries of `size` bytes starting at `offset` lies within `elf`.
+fn check_table(elf: &[u8], offset: u64, count: u32, size: u16) -> Result<()> {
+    if count != 0 && table_offset(offset, count, size)? > elf.len() {
+        Err(Error::UnexpectedEoF)
+    } else {
+        Ok(())
+    }
+}
+
 pub trait TableEntry<'a> where Self: Sized {
     type Header;
     fn new(elf: &'a [u8], header: &'a [u8]) -> Result<Self>;
 }
+/// Entries that are used as they are stored, such as symbols and relocations.
+impl<'a, T: View> TableEntry<'a> for &'a T {
+    type Header = T;
+    fn new(_: &'a [u8], header: &'a [u8]) -> Result<Self> {
+        T::view(header)
+    }
+}
 
 
 /// A program header and its associated data.
-pub struct Program<'a> {
-    pub header: &'a ProgramHeader,
+pub struct Program<'a, C: Class = Elf32> {
+    pub header: &'a C::ProgramHeader,
     pub data: &'a [u8]
 }
-impl<'a> Program<'a> {
-    pub fn new(header: &'a ProgramHeader, data: &'a [u8]) -> Self {
+impl<'a, C: Class> Clone for Program<'a, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'a, C: Class> Copy for Program<'a, C> {}
+impl<'a, C: Class> Program<'a, C> {
+    pub fn new(header: &'a C::ProgramHeader, data: &'a [u8]) -> Self {
         Self {
             header,
             data
@@ -405,69 +721,108 @@ impl<'a> Program<'a> {
     }
     /// Ensure that the type of the program header matches that expected, returning an `Err` otherwise.
     pub fn check_type(&self, ty: ProgramType) -> Result<()> {
-        if self.header.ty != ty {
-            Err(Error::WrongProgramType { expected: ty, actual: self.header.ty })
+        if self.header.ty() != ty {
+            Err(Error::WrongProgramType { expected: ty, actual: self.header.ty() })
         } else {
             Ok(())
         }
     }
+    /// Ensure that the program header has all of the given flags, returning an `Err` otherwise.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// let elf = Elf::from_bytes(include_bytes!("../examples/test.elf"))?;
+    /// // The second `PT_LOAD` segment holds `.text`, which is readable and executable (`PF_R | PF_X`)
+    /// let text = elf.program(2)?;
+    /// assert_eq!(u32::from(text.header.flags.get()), 0b101);
+    /// text.check_flag(ProgramFlags::Read | ProgramFlags::Exec)?;
+    /// assert!(text.check_flag(ProgramFlags::Write).is_err());
+    /// # Ok(()) })().unwrap()
+    /// ```
     pub fn check_flag(&self, flags: ProgramFlags) -> Result<()> {
-        if self.header.flags.all(flags) {
+        if self.header.flags().all(flags) {
             Ok(())
         } else {
-            Err(Error::WrongProgramFlags { expected: flags, actual: self.header.flags })
+            Err(Error::WrongProgramFlags { expected: flags, actual: self.header.flags() })
         }
     }
 }
-impl<'a> fmt::Debug for Program<'a> {
+impl<'a, C: Class> fmt::Debug for Program<'a, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.header.fmt(f)
     }
 }
-impl<'a> TableEntry<'a> for Program<'a> {
-    type Header = ProgramHeader;
+impl<'a, C: Class> TableEntry<'a> for Program<'a, C> {
+    type Header = C::ProgramHeader;
     fn new(elf: &'a [u8], header: &'a [u8]) -> Result<Self> {
-        let header = ProgramHeader::new(header)?;
+        let header = C::ProgramHeader::view(header)?;
         Ok(Self::new(header, header.data(elf)?))
     }
 }
-#[derive(Debug)]
-#[repr(C)]
-pub struct ProgramHeader {
-    pub ty: ProgramType,
-    pub offset: Offset,
-    pub virt_addr: Address,
-    pub phys_addr: Address,
-    pub file_size: u32,
-    pub mem_size: u32,
-    pub flags: ProgramFlags,
-    pub align: u32
+view!{
+    #[derive(Debug)]
+    pub struct ProgramHeader {
+        pub ty: Le<ProgramType>,
+        pub offset: Le<Offset>,
+        pub virt_addr: Le<Address>,
+        pub phys_addr: Le<Address>,
+        pub file_size: Le<u32>,
+        pub mem_size: Le<u32>,
+        pub flags: Le<ProgramFlags>,
+        pub align: Le<u32>
+    }
 }
 impl ProgramHeader {
     pub fn new(header: &[u8]) -> Result<&Self> {
-        if header.len() < size_of::<Self>() {
-            return Err(Error::UnexpectedEoF)
-        }
-        if header.as_ptr() as usize & 0b11 != 0 {
-            return Err(Error::Unaligned)
-        }
-        let header = unsafe { &*(header.as_ptr() as *const ProgramHeader) };
-        Ok(header)
+        Self::view(header)
     }
-    pub fn data<'a>(&'a self, elf: &'a [u8]) -> Result<&'a [u8]> {
-        let size: usize = self.file_size.try_into().map_err(|_| Error::IntegerOverflow)?;
-        let offset = self.offset.as_usize()?;
-        elf.get(offset..offset + size).ok_or(Error::UnexpectedEoF)
+}
+impl ElfProgramHeader<Elf32> for ProgramHeader {
+    fields!(
+        ty: ProgramType, flags: ProgramFlags, offset: Offset, virt_addr: Address, phys_addr: Address, file_size: u32,
+        mem_size: u32, align: u32
+    );
+}
+view!{
+    /// The ELF64 program header, which moves `flags` up to keep the 64-bit fields aligned.
+    #[derive(Debug)]
+    pub struct ProgramHeader64 {
+        pub ty: Le<ProgramType>,
+        pub flags: Le<ProgramFlags>,
+        pub offset: Le<Offset<u64>>,
+        pub virt_addr: Le<Address<u64>>,
+        pub phys_addr: Le<Address<u64>>,
+        pub file_size: Le<u64>,
+        pub mem_size: Le<u64>,
+        pub align: Le<u64>
+    }
+}
+impl ProgramHeader64 {
+    pub fn new(header: &[u8]) -> Result<&Self> {
+        Self::view(header)
     }
 }
+impl ElfProgramHeader<Elf64> for ProgramHeader64 {
+    fields!(
+        ty: ProgramType, flags: ProgramFlags, offset: Offset<u64>, virt_addr: Address<u64>, phys_addr: Address<u64>,
+        file_size: u64, mem_size: u64, align: u64
+    );
+}
 
 /// A section header and its associated data.
-pub struct Section<'a> {
-    pub header: &'a SectionHeader,
+pub struct Section<'a, C: Class = Elf32> {
+    pub header: &'a C::SectionHeader,
     pub data: &'a [u8]
 }
-impl<'a> Section<'a> {
-    pub fn new(header: &'a SectionHeader, data: &'a [u8]) -> Self {
+impl<'a, C: Class> Clone for Section<'a, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'a, C: Class> Copy for Section<'a, C> {}
+impl<'a, C: Class> Section<'a, C> {
+    pub fn new(header: &'a C::SectionHeader, data: &'a [u8]) -> Self {
         Self {
             header,
             data
@@ -475,73 +830,118 @@ impl<'a> Section<'a> {
     }
     /// Ensure that the type of the section matches that expected, returning an `Err` otherwise.
     pub fn check_type(&self, ty: SectionType) -> Result<()> {
-        if self.header.ty != ty {
-            Err(Error::WrongSectionType { expected: ty, actual: self.header.ty })
+        if self.header.ty() != ty {
+            Err(Error::WrongSectionType { expected: ty, actual: self.header.ty() })
         } else {
             Ok(())
         }
     }
+    /// Ensure that the section header has all of the given flags, returning an `Err` otherwise.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// let file = include_bytes!("../examples/test64.elf");
+    /// let elf = Elf::<Elf64>::parse(file)?;
+    /// elf.section(1)?.check_flag(SectionFlags::Alloc | SectionFlags::Exec)?;
+    ///
+    /// // Set a bit in the upper half of the `sh_flags` of `.text`, where no flags are defined
+    /// let mut file = file.to_vec();
+    /// file[elf.header.sh_offset.get().as_usize()? + 64 + 12] = 1;
+    /// let elf = Elf::<Elf64>::parse(&file)?;
+    /// assert!(matches!(elf.section(1)?.check_flag(SectionFlags::Alloc), Err(Error::IntegerOverflow)));
+    /// # Ok(()) })().unwrap()
+    /// ```
     pub fn check_flag(&self, flags: SectionFlags) -> Result<()> {
-        if self.header.flags.all(flags) {
+        let actual = self.header.flags()?;
+        if actual.all(flags) {
             Ok(())
         } else {
-            Err(Error::WrongSectionFlags { expected: flags, actual: self.header.flags })
+            Err(Error::WrongSectionFlags { expected: flags, actual })
         }
     }
 }
-impl<'a> fmt::Debug for Section<'a> {
+impl<'a, C: Class> fmt::Debug for Section<'a, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.header.fmt(f)
     }
 }
-impl<'a> TableEntry<'a> for Section<'a> {
-    type Header = SectionHeader;
+impl<'a, C: Class> TableEntry<'a> for Section<'a, C> {
+    type Header = C::SectionHeader;
     fn new(elf: &'a [u8], header: &'a [u8]) -> Result<Self> {
-        let header = SectionHeader::new(header)?;
+        let header = C::SectionHeader::view(header)?;
         Ok(Self::new(header, header.data(elf)?))
     }
 }
-#[derive(Debug)]
-#[repr(C)]
-pub struct SectionHeader {
-    pub name: u32,
-    pub ty: SectionType,
-    pub flags: SectionFlags,
-    pub address: Address,
-    pub offset: Offset,
-    pub size: u32,
-    pub link: u32,
-    pub info: u32,
-    pub alignment: u32,
-    pub entry_size: u32
+view!{
+    #[derive(Debug)]
+    pub struct SectionHeader {
+        pub name: Le<u32>,
+        pub ty: Le<SectionType>,
+        pub flags: Le<SectionFlags>,
+        pub address: Le<Address>,
+        pub offset: Le<Offset>,
+        pub size: Le<u32>,
+        pub link: Le<u32>,
+        pub info: Le<u32>,
+        pub alignment: Le<u32>,
+        pub entry_size: Le<u32>
+    }
 }
 impl SectionHeader {
     pub fn new(header: &[u8]) -> Result<&Self> {
-        if header.len() < size_of::<Self>() {
-            return Err(Error::UnexpectedEoF)
-        }
-        if header.as_ptr() as usize & 0b11 != 0 {
-            return Err(Error::Unaligned)
-        }
-        let header = unsafe { &*(header.as_ptr() as *const SectionHeader) };
-        Ok(header)
+        Self::view(header)
+    }
+}
+impl ElfSectionHeader<Elf32> for SectionHeader {
+    fn flags(&self) -> Result<SectionFlags> {
+        Ok(self.flags.get())
+    }
+    fields!(
+        name: u32, ty: SectionType, address: Address, offset: Offset, size: u32, link: u32, info: u32,
+        alignment: u32, entry_size: u32
+    );
+}
+view!{
+    /// The ELF64 section header.
+    #[derive(Debug)]
+    pub struct SectionHeader64 {
+        pub name: Le<u32>,
+        pub ty: Le<SectionType>,
+        pub flags: Le<u64>,
+        pub address: Le<Address<u64>>,
+        pub offset: Le<Offset<u64>>,
+        pub size: Le<u64>,
+        pub link: Le<u32>,
+        pub info: Le<u32>,
+        pub alignment: Le<u64>,
+        pub entry_size: Le<u64>
     }
-    pub fn data<'a>(&'a self, elf: &'a [u8]) -> Result<&'a [u8]> {
-        let size: usize = self.size.try_into().map_err(|_| Error::IntegerOverflow)?;
-        let offset = self.offset.as_usize()?;
-        elf.get(offset..offset + size).ok_or(Error::UnexpectedEoF)
+}
+impl SectionHeader64 {
+    pub fn new(header: &[u8]) -> Result<&Self> {
+        Self::view(header)
     }
 }
+impl ElfSectionHeader<Elf64> for SectionHeader64 {
+    fn flags(&self) -> Result<SectionFlags> {
+        u32::try_from(self.flags.get()).map(SectionFlags).map_err(|_| Error::IntegerOverflow)
+    }
+    fields!(
+        name: u32, ty: SectionType, address: Address<u64>, offset: Offset<u64>, size: u64, link: u32, info: u32,
+        alignment: u64, entry_size: u64
+    );
+}
 
 #[derive(Copy, Clone)]
 pub struct StringTable<'a>(&'a [u8]);
 impl<'a> StringTable<'a> {
     /// Coerce a Section into a string table.
     /// 
-    /// The section must be of type `SHT_STRTAB` and have the `SHF_STRINGS` flag.
-    pub fn new(section: Section<'a>) -> Result<Self> {
+    /// The section must be of type `SHT_STRTAB`. The `SHF_STRINGS` flag is not required as linkers do not set it on
+    /// `.strtab` or `.shstrtab`.
+    pub fn new<C: Class>(section: Section<'a, C>) -> Result<Self> {
         section.check_type(SectionType::StringTable)?;
-        section.check_flag(SectionFlags::Strings)?;
         Ok(Self(section.data))
     }
     pub fn get_str(self, index: u32) -> Result<&'a str> {
@@ -606,14 +1006,15 @@ c_enum!{
         ProgramHeader = 6,
         ThreadLocalStorage = 7,
         GnuStack = 0x6474E551,
+        GnuProperty = 0x6474E553,
         RiscVAttributes = 0x70000003
     } v => Err(Error::UnsupportedProgramType(Self(v)))
 }
 c_flags!{
     pub ProgramFlags(u32) {
-        Read = 0b001,
-        Exec = 0b010,
-        Write = 0b100
+        Exec = 0b001,
+        Write = 0b010,
+        Read = 0b100
     } v => Err(Error::UnsupportedProgramFlags(v))
 }
 c_enum!{
@@ -633,7 +1034,9 @@ c_enum!{
         FiniArray = 15,
         PreinitArray = 16,
         Group = 17,
-        SymbolIndex = 18
+        SymbolIndex = 18,
+        GnuHash = 0x6FFFFFF6,
+        RiscVAttributes = 0x70000003
     } v => Err(Error::UnsupportedSectionType(Self(v)))
 }
 c_flags!{
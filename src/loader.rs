@@ -0,0 +1,192 @@
+use core::ops::Range;
+use crate::{Elf, Error, FileType, Memory, Program, ProgramType, Result};
+
+/// Copies the `PT_LOAD` segments of an executable into memory at their virtual addresses.
+///
+/// Position-independent executables and shared objects may be moved by a load bias, after which their dynamic
+/// relocations must be applied with `Dynamic::relocate`.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// # let mut data = [0u32; 8192];
+/// # let elf = include_bytes!("../examples/test.elf");
+/// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+/// let elf = Elf::new(&data)?;
+/// let mut buffer = [0u8; 0x2000];
+/// let mut memory = Region::new(0x10000, &mut buffer);
+/// let loaded = Loader::new(&elf).load(&mut memory)?;
+/// assert_eq!(loaded, Loaded { entry: 0x110C2, start: 0x10000, end: 0x110E0 });
+/// let mut message = [0; 14];
+/// memory.read(0x100B4, &mut message)?;
+/// assert_eq!(&message, b"Hello, World!\n");
+/// # Ok(()) })().unwrap()
+/// ```
+#[derive(Debug)]
+pub struct Loader<'a, 'e> {
+    elf: &'e Elf<'a>,
+    bias: u32
+}
+impl<'a, 'e> Loader<'a, 'e> {
+    pub fn new(elf: &'e Elf<'a>) -> Self {
+        Self::with_bias(elf, 0)
+    }
+    /// Like `Loader::new` but adds `bias` to every address, which requires a shared object or PIE.
+    pub fn with_bias(elf: &'e Elf<'a>, bias: u32) -> Self {
+        Self {
+            elf,
+            bias
+        }
+    }
+    /// Validate every segment against the memory map `allowed` without writing anything.
+    ///
+    /// See `LoadPlan::new`.
+    pub fn plan(&self, allowed: &[Range<u32>]) -> Result<LoadPlan<'a, 'e>> {
+        LoadPlan::validate(self.elf, self.bias, Some(allowed))
+    }
+    /// Copy each segment's file data to its virtual address and zero-fill the remaining `mem_size - file_size` bytes.
+    ///
+    /// The segments are validated as with `Loader::plan` before anything is written, but may be placed anywhere in
+    /// the address space.
+    pub fn load(&self, memory: &mut impl Memory) -> Result<Loaded> {
+        LoadPlan::validate(self.elf, self.bias, None)?.commit(memory)
+    }
+}
+
+/// A validated set of `PT_LOAD` segments, ready to be copied into memory.
+///
+/// Validation happens up front so that a rejected image never leaves memory partially written.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// # let mut data = [0u32; 8192];
+/// # let elf = include_bytes!("../examples/test.elf");
+/// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+/// let elf = Elf::new(&data)?;
+/// // The image spans 0x10000..0x110E0 so does not fit in the first 4KiB of RAM
+/// assert!(matches!(LoadPlan::new(&elf, &[0x10000..0x11000]), Err(Error::SegmentOutOfBounds { .. })));
+///
+/// let plan = LoadPlan::new(&elf, &[0x10000..0x12000])?;
+/// let mut buffer = [0u8; 0x2000];
+/// let loaded = plan.commit(&mut Region::new(0x10000, &mut buffer))?;
+/// assert_eq!(loaded.entry, 0x110C2);
+/// # Ok(()) })().unwrap()
+/// ```
+#[derive(Debug)]
+pub struct LoadPlan<'a, 'e> {
+    elf: &'e Elf<'a>,
+    bias: u32,
+    loaded: Loaded
+}
+impl<'a, 'e> LoadPlan<'a, 'e> {
+    /// Validate every `PT_LOAD` segment of an executable or shared object.
+    ///
+    /// Each segment must have `file_size <= mem_size`, its file data must lie within the file, it must not overlap
+    /// another segment, and it must lie entirely within one of the `allowed` address ranges. Where `align` is greater
+    /// than 1 it must be a power of two with `offset % align == virt_addr % align`.
+    pub fn new(elf: &'e Elf<'a>, allowed: &[Range<u32>]) -> Result<Self> {
+        Self::validate(elf, 0, Some(allowed))
+    }
+    /// Like `LoadPlan::new` but adds `bias` to every address, which requires a shared object or PIE.
+    ///
+    /// The bias must preserve the alignment of every segment, so is usually a multiple of the page size.
+    pub fn with_bias(elf: &'e Elf<'a>, bias: u32, allowed: &[Range<u32>]) -> Result<Self> {
+        Self::validate(elf, bias, Some(allowed))
+    }
+    fn validate(elf: &'e Elf<'a>, bias: u32, allowed: Option<&[Range<u32>]>) -> Result<Self> {
+        if bias != 0 {
+            elf.check_type(FileType::SharedObject)?;
+        } else if elf.header.ty != FileType::SharedObject {
+            elf.check_type(FileType::Executable)?;
+        }
+        let mut start = u32::MAX;
+        let mut end = 0;
+        for (index, program) in elf.programs()?.enumerate() {
+            let program = program?;
+            let range = match segment(&program, bias)? {
+                Some(range) => range,
+                None => continue
+            };
+            let header = program.header;
+            if header.file_size.get() > header.mem_size.get() {
+                return Err(Error::InvalidSegmentSize)
+            }
+            let align = header.align.get();
+            if align > 1 {
+                if !align.is_power_of_two() {
+                    return Err(Error::MisalignedSegment { address: range.start })
+                }
+                if header.offset.get().0 % align != range.start % align {
+                    return Err(Error::MisalignedSegment { address: range.start })
+                }
+            }
+            if let Some(allowed) = allowed {
+                if !allowed.iter().any(|region| region.start <= range.start && range.end <= region.end) {
+                    return Err(Error::SegmentOutOfBounds { address: range.start })
+                }
+            }
+            for other in elf.programs()?.skip(index + 1) {
+                if let Some(other) = segment(&other?, bias)? {
+                    if range.start < other.end && other.start < range.end {
+                        return Err(Error::SegmentOverlap { address: range.start.max(other.start) })
+                    }
+                }
+            }
+            start = start.min(range.start);
+            end = end.max(range.end);
+        }
+        Ok(Self {
+            elf,
+            bias,
+            loaded: Loaded {
+                entry: elf.header.entry.get().0.checked_add(bias).ok_or(Error::IntegerOverflow)?,
+                start: start.min(end),
+                end
+            }
+        })
+    }
+    /// The amount added to every virtual address.
+    pub fn bias(&self) -> u32 {
+        self.bias
+    }
+    /// The entry point and extent the image will have once committed.
+    pub fn loaded(&self) -> Loaded {
+        self.loaded
+    }
+    /// Copy each segment's file data to its virtual address and zero-fill the remaining `mem_size - file_size` bytes.
+    pub fn commit(self, memory: &mut impl Memory) -> Result<Loaded> {
+        for program in self.elf.programs()? {
+            let program = program?;
+            if program.header.ty != ProgramType::Load {
+                continue
+            }
+            let header = program.header;
+            let address = header.virt_addr.get().0 + self.bias;
+            memory.write(address, program.data)?;
+            memory.fill(address + header.file_size.get(), header.mem_size.get() - header.file_size.get(), 0)?;
+        }
+        Ok(self.loaded)
+    }
+}
+
+/// The address range occupied by a `PT_LOAD` segment once biased, or `None` for any other kind of segment.
+fn segment(program: &Program, bias: u32) -> Result<Option<Range<u32>>> {
+    if program.header.ty != ProgramType::Load {
+        return Ok(None)
+    }
+    let start = program.header.virt_addr.get().0.checked_add(bias).ok_or(Error::IntegerOverflow)?;
+    let end = start.checked_add(program.header.mem_size.get()).ok_or(Error::IntegerOverflow)?;
+    Ok(Some(start..end))
+}
+
+/// The result of loading an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Loaded {
+    /// The address to begin execution at.
+    pub entry: u32,
+    /// The lowest address written to.
+    pub start: u32,
+    /// One past the highest address written to.
+    pub end: u32
+}
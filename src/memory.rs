@@ -0,0 +1,107 @@
+use core::ops::Range;
+use crate::{Error, Result};
+
+/// Memory that a loader places an image into, addressed by the addresses the image will run at.
+///
+/// A kernel might implement this by mapping pages on demand, while a bootloader can write straight to physical memory.
+pub trait Memory {
+    /// Copy `data` to `address`.
+    fn write(&mut self, address: u32, data: &[u8]) -> Result<()>;
+    /// Read `data.len()` bytes from `address` into `data`.
+    fn read(&mut self, address: u32, data: &mut [u8]) -> Result<()>;
+    /// Set `len` bytes at `address` to `value`.
+    fn fill(&mut self, address: u32, len: u32, value: u8) -> Result<()> {
+        let chunk = [value; 64];
+        let mut done = 0;
+        while done < len {
+            let size = (len - done).min(chunk.len() as u32);
+            self.write(address.checked_add(done).ok_or(Error::IntegerOverflow)?, &chunk[..size as usize])?;
+            done += size;
+        }
+        Ok(())
+    }
+    /// Read a byte from `address`.
+    fn read_u8(&mut self, address: u32) -> Result<u8> {
+        let mut bytes = [0; 1];
+        self.read(address, &mut bytes)?;
+        Ok(bytes[0])
+    }
+    /// Read a little-endian half-word from `address`.
+    fn read_u16(&mut self, address: u32) -> Result<u16> {
+        let mut bytes = [0; 2];
+        self.read(address, &mut bytes)?;
+        Ok(u16::from_le_bytes(bytes))
+    }
+    /// Read a little-endian word from `address`.
+    fn read_u32(&mut self, address: u32) -> Result<u32> {
+        let mut bytes = [0; 4];
+        self.read(address, &mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+    /// Read a little-endian double-word from `address`.
+    fn read_u64(&mut self, address: u32) -> Result<u64> {
+        let mut bytes = [0; 8];
+        self.read(address, &mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+    /// Write a byte to `address`.
+    fn write_u8(&mut self, address: u32, value: u8) -> Result<()> {
+        self.write(address, &[value])
+    }
+    /// Write a little-endian half-word to `address`.
+    fn write_u16(&mut self, address: u32, value: u16) -> Result<()> {
+        self.write(address, &value.to_le_bytes())
+    }
+    /// Write a little-endian word to `address`.
+    fn write_u32(&mut self, address: u32, value: u32) -> Result<()> {
+        self.write(address, &value.to_le_bytes())
+    }
+    /// Write a little-endian double-word to `address`.
+    fn write_u64(&mut self, address: u32, value: u64) -> Result<()> {
+        self.write(address, &value.to_le_bytes())
+    }
+}
+
+/// A contiguous byte slice standing in for the memory at `base..base + memory.len()`.
+///
+/// Useful for loading into a buffer or for tests.
+#[derive(Debug)]
+pub struct Region<'m> {
+    pub base: u32,
+    pub memory: &'m mut [u8]
+}
+impl<'m> Region<'m> {
+    pub fn new(base: u32, memory: &'m mut [u8]) -> Self {
+        Self {
+            base,
+            memory
+        }
+    }
+    /// Convert an address range into a range of indices into `memory`.
+    fn range(&self, address: u32, len: usize) -> Result<Range<usize>> {
+        let start = address.checked_sub(self.base).ok_or(Error::AddressOutOfRange)? as usize;
+        let end = start.checked_add(len).ok_or(Error::IntegerOverflow)?;
+        if end > self.memory.len() {
+            Err(Error::AddressOutOfRange)
+        } else {
+            Ok(start..end)
+        }
+    }
+}
+impl<'m> Memory for Region<'m> {
+    fn write(&mut self, address: u32, data: &[u8]) -> Result<()> {
+        let range = self.range(address, data.len())?;
+        self.memory[range].copy_from_slice(data);
+        Ok(())
+    }
+    fn read(&mut self, address: u32, data: &mut [u8]) -> Result<()> {
+        let range = self.range(address, data.len())?;
+        data.copy_from_slice(&self.memory[range]);
+        Ok(())
+    }
+    fn fill(&mut self, address: u32, len: u32, value: u8) -> Result<()> {
+        let range = self.range(address, len as usize)?;
+        self.memory[range].fill(value);
+        Ok(())
+    }
+}
@@ -0,0 +1,459 @@
+use crate::{
+    bytes, Elf, ElfSymbol, Error, FileType, Memory, RelaTable, Relocation, RelocationType, Result, Section,
+    SectionFlags, SectionType, SymbolBinding, SymbolSection, SymbolTable
+};
+
+/// Loads a relocatable object (`ET_REL`) as a module.
+///
+/// Loading happens in three steps: `place` chooses an address for each `SHF_ALLOC` section, `load` copies the sections
+/// into memory and `relocate` patches them, resolving undefined symbols through a callback.
+///
+/// Linker relaxation is not performed. `R_RISCV_RELAX` is ignored as the unrelaxed sequence is always valid, and
+/// `R_RISCV_ALIGN` is accepted only where the padding left in place already produces the requested alignment.
+/// `R_RISCV_TPREL_ADD` only marks an instruction for relaxation, so is ignored too.
+///
+/// Thread-local symbols are addressed by their offset from the lowest placed `SHF_TLS` section, so the placed `.tdata`
+/// and `.tbss` sections are the initial image of a TLS block that the thread pointer points to. There is no GOT, so
+/// the relocations emitted for `-fPIC` code and the general and initial-exec TLS models fail with `Error::MissingGot`.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// # let mut data = [0u32; 8192];
+/// # let elf = include_bytes!("../examples/module.o");
+/// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+/// let elf = Elf::new(&data)?;
+/// let mut buffer = [0u8; 256];
+/// let mut memory = Region::new(0x8000_0000, &mut buffer);
+/// let mut addresses = [None; 16];
+/// let mut loader = ModuleLoader::new(&elf, &mut addresses)?;
+/// let mut next = 0x8000_0000u32;
+/// loader.place(|_, section| {
+///     let align = section.header.alignment.get().max(1);
+///     let address = (next + align - 1) / align * align;
+///     next = address + section.header.size.get();
+///     Ok(address)
+/// })?;
+/// loader.load(&mut memory)?;
+/// loader.relocate(&mut memory, |name| match name {
+///     "print" => Some(0x8000_1000),
+///     _ => None
+/// })?;
+///
+/// let init = loader.symbol("module_init")?.unwrap();
+/// let counter = loader.symbol("counter")?.unwrap();
+/// // `table` in `.data` holds the addresses of `helper` and `message`
+/// assert_eq!(memory.read_u32(counter + 4)?, init + 0x2E);
+/// # Ok(()) })().unwrap()
+/// ```
+pub struct ModuleLoader<'a, 'l> {
+    elf: &'l Elf<'a>,
+    addresses: &'l mut [Option<u32>]
+}
+impl<'a, 'l> ModuleLoader<'a, 'l> {
+    /// Create a loader for a relocatable object.
+    ///
+    /// `addresses` holds the address of each section once placed and must have an entry for every section header.
+    pub fn new(elf: &'l Elf<'a>, addresses: &'l mut [Option<u32>]) -> Result<Self> {
+        elf.check_type(FileType::Relocatable)?;
+        let addresses = addresses.get_mut(..elf.section_count() as usize).ok_or(Error::IndexOutOfRange)?;
+        addresses.fill(None);
+        Ok(Self {
+            elf,
+            addresses
+        })
+    }
+    /// Choose an address for every `SHF_ALLOC` section.
+    ///
+    /// `place` is called once for each such section in section header order with the section index, and must return
+    /// an address that satisfies the section's alignment.
+    pub fn place(&mut self, mut place: impl FnMut(u32, &Section<'a>) -> Result<u32>) -> Result<()> {
+        for (index, section) in self.elf.sections()?.enumerate() {
+            let section = section?;
+            if !section.header.flags.get().all(SectionFlags::Alloc) {
+                continue
+            }
+            let address = place(index as u32, &section)?;
+            if section.header.alignment.get() > 1 && !address.is_multiple_of(section.header.alignment.get()) {
+                return Err(Error::Unaligned)
+            }
+            address.checked_add(section.header.size.get()).ok_or(Error::IntegerOverflow)?;
+            self.addresses[index] = Some(address);
+        }
+        Ok(())
+    }
+    /// Get the address a section was placed at.
+    pub fn section_address(&self, index: u32) -> Option<u32> {
+        self.addresses.get(index as usize).copied().flatten()
+    }
+    /// Copy each placed section into memory, zero-filling `SHT_NOBITS` sections such as `.bss`.
+    pub fn load(&self, memory: &mut impl Memory) -> Result<()> {
+        for (index, section) in self.elf.sections()?.enumerate() {
+            let section = section?;
+            if let Some(address) = self.addresses[index] {
+                if section.header.ty == SectionType::NoBits {
+                    memory.fill(address, section.header.size.get(), 0)?;
+                } else {
+                    memory.write(address, section.data)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Apply the relocations for every placed section.
+    ///
+    /// Undefined symbols are looked up by name through `resolve`. An undefined weak symbol that cannot be resolved
+    /// has the value 0, any other is reported as `Error::UnresolvedSymbol`. For an undefined thread-local symbol,
+    /// `resolve` must return its offset from the thread pointer.
+    pub fn relocate(&self, memory: &mut impl Memory, mut resolve: impl FnMut(&str) -> Option<u32>) -> Result<()> {
+        let tls = self.tls_base()?;
+        for section in self.elf.sections()? {
+            let section = section?;
+            match section.header.ty.get() {
+                SectionType::Rela => {
+                    let table = RelaTable::new(self.elf, section)?;
+                    if let Some(base) = self.section_address(table.target_index) {
+                        self.relocate_table(&table, base, tls, memory, &mut resolve)?;
+                    }
+                }
+                SectionType::Rel => return Err(Error::UnsupportedSectionType(SectionType::Rel)),
+                _ => ()
+            }
+        }
+        Ok(())
+    }
+    /// Get the address of a symbol defined by the module.
+    pub fn symbol(&self, name: &str) -> Result<Option<u32>> {
+        let symbols = match self.elf.symbol_table()? {
+            Some(symbols) => symbols,
+            None => return Ok(None)
+        };
+        match symbols.find(name)? {
+            Some((index, symbol)) if !symbol.is_undefined() => {
+                self.symbol_value(Some(&symbols), index, &mut |_| None).map(Some)
+            }
+            _ => Ok(None)
+        }
+    }
+
+    fn symbol_value(
+        &self,
+        symbols: Option<&SymbolTable<'a>>,
+        index: u32,
+        resolve: &mut impl FnMut(&str) -> Option<u32>
+    ) -> Result<u32> {
+        if index == 0 {
+            return Ok(0)
+        }
+        let symbols = symbols.ok_or(Error::MissingSymbolTable)?;
+        let symbol = symbols.get(index)?;
+        match symbols.section(index)? {
+            SymbolSection::Undefined => match resolve(symbols.name(symbol)?) {
+                Some(value) => Ok(value),
+                None if symbol.binding() == SymbolBinding::Weak => Ok(0),
+                None => Err(Error::UnresolvedSymbol(index))
+            },
+            SymbolSection::Absolute => Ok(symbol.value.get().0),
+            SymbolSection::Index(section) => self.section_address(section)
+                .map(|base| base.wrapping_add(symbol.value.get().0))
+                .ok_or(Error::UnresolvedSymbol(index)),
+            section => Err(Error::UnsupportedSymbolSection(section))
+        }
+    }
+    fn relocate_table(
+        &self,
+        table: &RelaTable<'a>,
+        base: u32,
+        tls: Option<u32>,
+        memory: &mut impl Memory,
+        resolve: &mut impl FnMut(&str) -> Option<u32>
+    ) -> Result<()> {
+        for (index, rela) in table.relocations()?.enumerate() {
+            let rela = rela?;
+            let ty = rela.ty();
+            let p = base.wrapping_add(rela.offset.get().0);
+            let s = match ty {
+                RelocationType::PcrelLo12I | RelocationType::PcrelLo12S => {
+                    // The symbol labels the `auipc` holding the upper bits, which must be recomputed to find the lower
+                    let label = self.symbol_value(table.symbols.as_ref(), rela.symbol(), resolve)?;
+                    self.pcrel_hi20(table, index as u32, base, label, resolve)?
+                }
+                RelocationType::TpRelHi20 | RelocationType::TpRelLo12I | RelocationType::TpRelLo12S => {
+                    self.tp_offset(table.symbols.as_ref(), rela.symbol(), tls, resolve)?
+                }
+                _ => self.symbol_value(table.symbols.as_ref(), rela.symbol(), resolve)?
+            };
+            apply(memory, ty, p, s, rela.addend.get())?;
+        }
+        Ok(())
+    }
+    /// The address of the module's TLS block, which is the lowest placed `SHF_TLS` section.
+    fn tls_base(&self) -> Result<Option<u32>> {
+        let mut base = None;
+        for (index, section) in self.elf.sections()?.enumerate() {
+            let section = section?;
+            let Some(address) = self.addresses[index] else { continue };
+            if section.header.flags.get().all(SectionFlags::Tls) {
+                base = Some(base.map_or(address, |base: u32| base.min(address)));
+            }
+        }
+        Ok(base)
+    }
+    /// The offset of a thread-local symbol from the thread pointer, given the address of the TLS block.
+    fn tp_offset(
+        &self,
+        symbols: Option<&SymbolTable<'a>>,
+        index: u32,
+        tls: Option<u32>,
+        resolve: &mut impl FnMut(&str) -> Option<u32>
+    ) -> Result<u32> {
+        let value = self.symbol_value(symbols, index, resolve)?;
+        match symbols.map(|symbols| symbols.section(index)).transpose()? {
+            Some(SymbolSection::Index(_)) => Ok(value.wrapping_sub(tls.ok_or(Error::UnresolvedSymbol(index))?)),
+            // Undefined symbols are resolved to their offset directly
+            _ => Ok(value)
+        }
+    }
+    /// Find the `R_RISCV_PCREL_HI20` at `address` and return the full PC-relative offset it encodes.
+    ///
+    /// Assemblers emit the `R_RISCV_PCREL_HI20` before the relocations that refer to it, usually just before, so the
+    /// table is searched backwards from the entry at `before`.
+    fn pcrel_hi20(
+        &self,
+        table: &RelaTable<'a>,
+        before: u32,
+        base: u32,
+        address: u32,
+        resolve: &mut impl FnMut(&str) -> Option<u32>
+    ) -> Result<u32> {
+        for index in (0..before).rev() {
+            let hi = table.get(index)?;
+            if hi.ty() == RelocationType::PcrelHi20 && base.wrapping_add(hi.offset.get().0) == address {
+                let s = self.symbol_value(table.symbols.as_ref(), hi.symbol(), resolve)?;
+                return Ok(s.wrapping_add(hi.addend.get() as u32).wrapping_sub(address))
+            }
+        }
+        Err(Error::MissingPcrelHi20 { address })
+    }
+}
+
+/// Apply a single relocation of type `ty` at `p` against a symbol with value `s`.
+fn apply(memory: &mut impl Memory, ty: RelocationType, p: u32, s: u32, a: i32) -> Result<()> {
+    let value = s.wrapping_add(a as u32);
+    let pcrel = value.wrapping_sub(p);
+    let out_of_range = Error::RelocationOutOfRange { ty, address: p };
+    match ty {
+        RelocationType::None | RelocationType::Relax | RelocationType::TpRelAdd => (),
+        RelocationType::Align => {
+            // The padding is executable as-is, so only check that the code following it is aligned.
+            let alignment = (a as u32).wrapping_add(1).checked_next_power_of_two();
+            if !alignment.is_some_and(|alignment| p.wrapping_add(a as u32).is_multiple_of(alignment)) {
+                return Err(Error::MisalignedRelocation { ty, address: p })
+            }
+        }
+        RelocationType::Abs32 => memory.write_u32(p, value)?,
+        RelocationType::Abs64 => memory.write_u64(p, (s as u64).wrapping_add(a as i64 as u64))?,
+        RelocationType::Pcrel32 | RelocationType::Plt32 => memory.write_u32(p, pcrel)?,
+        RelocationType::Branch => {
+            let offset = pcrel as i32;
+            check_jump(ty, p, offset, 13)?;
+            let insn = memory.read_u32(p)? & 0x01FF_F07F;
+            memory.write_u32(p, insn | encode_b(offset as u32))?
+        }
+        RelocationType::Jal => {
+            let offset = pcrel as i32;
+            check_jump(ty, p, offset, 21)?;
+            let insn = memory.read_u32(p)? & 0xFFF;
+            memory.write_u32(p, insn | encode_j(offset as u32))?
+        }
+        RelocationType::Call | RelocationType::CallPlt => {
+            let auipc = memory.read_u32(p)? & 0xFFF;
+            memory.write_u32(p, auipc | hi20(pcrel))?;
+            let jalr_at = p.wrapping_add(4);
+            let jalr = memory.read_u32(jalr_at)? & 0x000F_FFFF;
+            memory.write_u32(jalr_at, jalr | encode_i(pcrel))?
+        }
+        RelocationType::PcrelHi20 => {
+            let insn = memory.read_u32(p)? & 0xFFF;
+            memory.write_u32(p, insn | hi20(pcrel))?
+        }
+        // For the TP-relative relocations `value` holds the symbol's offset from the thread pointer
+        RelocationType::Hi20 | RelocationType::TpRelHi20 => {
+            let insn = memory.read_u32(p)? & 0xFFF;
+            memory.write_u32(p, insn | hi20(value))?
+        }
+        // For the PC-relative pair `value` already holds the offset computed for the matching `R_RISCV_PCREL_HI20`
+        RelocationType::PcrelLo12I | RelocationType::Lo12I | RelocationType::TpRelLo12I => {
+            let insn = memory.read_u32(p)? & 0x000F_FFFF;
+            memory.write_u32(p, insn | encode_i(value))?
+        }
+        RelocationType::PcrelLo12S | RelocationType::Lo12S | RelocationType::TpRelLo12S => {
+            let insn = memory.read_u32(p)? & 0x01FF_F07F;
+            memory.write_u32(p, insn | encode_s(value))?
+        }
+        RelocationType::RvcBranch => {
+            let offset = pcrel as i32;
+            check_jump(ty, p, offset, 9)?;
+            let insn = memory.read_u16(p)? & 0xE383;
+            memory.write_u16(p, insn | encode_cb(offset as u32))?
+        }
+        RelocationType::RvcJump => {
+            let offset = pcrel as i32;
+            check_jump(ty, p, offset, 12)?;
+            let insn = memory.read_u16(p)? & 0xE003;
+            memory.write_u16(p, insn | encode_cj(offset as u32))?
+        }
+        RelocationType::RvcLui => {
+            let imm = (hi20(value) as i32) >> 12;
+            if !(-32..32).contains(&imm) {
+                return Err(out_of_range)
+            }
+            let insn = memory.read_u16(p)?;
+            if imm == 0 {
+                // `c.lui rd, 0` is reserved, so load the zero with `c.li rd, 0` instead
+                memory.write_u16(p, (insn & 0x0F83) | 0x4000)?
+            } else {
+                memory.write_u16(p, (insn & 0xEF83) | encode_ci(imm as u32))?
+            }
+        }
+        RelocationType::GotHi20
+        | RelocationType::Got32Pcrel
+        | RelocationType::TlsGotHi20
+        | RelocationType::TlsGdHi20
+        | RelocationType::TlsDescHi20
+        | RelocationType::TlsDescLoadLo12
+        | RelocationType::TlsDescAddLo12
+        | RelocationType::TlsDescCall => return Err(Error::MissingGot { ty, address: p }),
+        RelocationType::Add8 => {
+            let old = memory.read_u8(p)?;
+            memory.write_u8(p, old.wrapping_add(value as u8))?
+        }
+        RelocationType::Add16 => {
+            let old = memory.read_u16(p)?;
+            memory.write_u16(p, old.wrapping_add(value as u16))?
+        }
+        RelocationType::Add32 => {
+            let old = memory.read_u32(p)?;
+            memory.write_u32(p, old.wrapping_add(value))?
+        }
+        RelocationType::Add64 => {
+            let old = memory.read_u64(p)?;
+            memory.write_u64(p, old.wrapping_add(value as u64))?
+        }
+        RelocationType::Sub6 => {
+            let byte = memory.read_u8(p)?;
+            memory.write_u8(p, (byte & 0xC0) | (byte.wrapping_sub(value as u8) & 0x3F))?
+        }
+        RelocationType::Sub8 => {
+            let old = memory.read_u8(p)?;
+            memory.write_u8(p, old.wrapping_sub(value as u8))?
+        }
+        RelocationType::Sub16 => {
+            let old = memory.read_u16(p)?;
+            memory.write_u16(p, old.wrapping_sub(value as u16))?
+        }
+        RelocationType::Sub32 => {
+            let old = memory.read_u32(p)?;
+            memory.write_u32(p, old.wrapping_sub(value))?
+        }
+        RelocationType::Sub64 => {
+            let old = memory.read_u64(p)?;
+            memory.write_u64(p, old.wrapping_sub(value as u64))?
+        }
+        RelocationType::Set6 => {
+            let byte = memory.read_u8(p)?;
+            memory.write_u8(p, (byte & 0xC0) | (value as u8 & 0x3F))?
+        }
+        RelocationType::Set8 => memory.write_u8(p, value as u8)?,
+        RelocationType::Set16 => memory.write_u16(p, value as u16)?,
+        RelocationType::Set32 => memory.write_u32(p, value)?,
+        RelocationType::SetUleb128 => write_uleb128(memory, p, value).map_err(|_| out_of_range)?,
+        RelocationType::SubUleb128 => {
+            let old = read_uleb128(memory, p)?;
+            write_uleb128(memory, p, old.wrapping_sub(value)).map_err(|_| out_of_range)?
+        }
+        _ => return Err(Error::UnsupportedRelocationType(ty))
+    }
+    Ok(())
+}
+
+/// Check that a PC-relative jump is even and fits in a signed immediate of `bits` bits.
+fn check_jump(ty: RelocationType, address: u32, offset: i32, bits: u32) -> Result<()> {
+    let limit = 1i32 << (bits - 1);
+    if offset & 1 != 0 {
+        Err(Error::MisalignedRelocation { ty, address })
+    } else if offset < -limit || offset >= limit {
+        Err(Error::RelocationOutOfRange { ty, address })
+    } else {
+        Ok(())
+    }
+}
+
+/// The upper 20 bits of `value` for a U-type immediate, rounded to account for the sign of the lower 12 bits.
+fn hi20(value: u32) -> u32 {
+    value.wrapping_add(0x800) & 0xFFFF_F000
+}
+fn encode_i(value: u32) -> u32 {
+    (value & 0xFFF) << 20
+}
+fn encode_s(value: u32) -> u32 {
+    ((value & 0xFE0) << 20) | ((value & 0x1F) << 7)
+}
+fn encode_b(offset: u32) -> u32 {
+    ((offset >> 12 & 0x1) << 31)
+        | ((offset >> 5 & 0x3F) << 25)
+        | ((offset >> 1 & 0xF) << 8)
+        | ((offset >> 11 & 0x1) << 7)
+}
+fn encode_j(offset: u32) -> u32 {
+    ((offset >> 20 & 0x1) << 31)
+        | ((offset >> 1 & 0x3FF) << 21)
+        | ((offset >> 11 & 0x1) << 20)
+        | ((offset >> 12 & 0xFF) << 12)
+}
+fn encode_ci(imm: u32) -> u16 {
+    (((imm >> 5 & 0x1) << 12) | ((imm & 0x1F) << 2)) as u16
+}
+fn encode_cb(offset: u32) -> u16 {
+    (((offset >> 8 & 0x1) << 12)
+        | ((offset >> 3 & 0x3) << 10)
+        | ((offset >> 6 & 0x3) << 5)
+        | ((offset >> 1 & 0x3) << 3)
+        | ((offset >> 5 & 0x1) << 2)) as u16
+}
+fn encode_cj(offset: u32) -> u16 {
+    (((offset >> 11 & 0x1) << 12)
+        | ((offset >> 4 & 0x1) << 11)
+        | ((offset >> 8 & 0x3) << 9)
+        | ((offset >> 10 & 0x1) << 8)
+        | ((offset >> 6 & 0x1) << 7)
+        | ((offset >> 7 & 0x1) << 6)
+        | ((offset >> 1 & 0x7) << 3)
+        | ((offset >> 5 & 0x1) << 2)) as u16
+}
+
+/// Read the ULEB128 value at `address`, which must fit in 32 bits.
+fn read_uleb128(memory: &mut impl Memory, address: u32) -> Result<u32> {
+    // Read a byte at a time, as the value may end at the last byte of memory
+    let mut bytes = [0; 5];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = memory.read_u8(address.wrapping_add(i as u32))?;
+        if *byte & 0x80 == 0 {
+            break
+        }
+    }
+    bytes::read_uleb128(&bytes).map(|(value, _)| value)
+}
+/// Overwrite the ULEB128 value at `address` with `value`, keeping the encoded length unchanged.
+fn write_uleb128(memory: &mut impl Memory, address: u32, mut value: u32) -> Result<()> {
+    for i in 0..5 {
+        let at = address.wrapping_add(i);
+        let more = memory.read_u8(at)? & 0x80;
+        memory.write_u8(at, (value & 0x7F) as u8 | more)?;
+        value >>= 7;
+        if more == 0 {
+            return if value == 0 { Ok(()) } else { Err(Error::IntegerOverflow) }
+        }
+    }
+    Err(Error::IntegerOverflow)
+}
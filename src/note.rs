@@ -0,0 +1,321 @@
+use core::fmt;
+use crate::{
+    bytes::read_u32, Class, Elf, ElfProgramHeader, ElfSectionHeader, Error, Le, Program, ProgramType, Result, Section,
+    SectionType, View
+};
+
+impl<'a, C: Class> Elf<'a, C> {
+    /// Find the first note with the given owner `name` and type.
+    ///
+    /// The `PT_NOTE` segments are searched first so that files without section headers are still supported, then the
+    /// `SHT_NOTE` sections.
+    pub fn find_note(&self, name: &[u8], ty: u32) -> Result<Option<Note<'a>>> {
+        for program in self.programs()? {
+            let program = program?;
+            if program.header.ty() != ProgramType::Note {
+                continue
+            }
+            if let Some(note) = NoteIter::from_program(program)?.find_note(name, ty)? {
+                return Ok(Some(note))
+            }
+        }
+        for section in self.sections()? {
+            let section = section?;
+            if section.header.ty() != SectionType::Note {
+                continue
+            }
+            if let Some(note) = NoteIter::from_section(section)?.find_note(name, ty)? {
+                return Ok(Some(note))
+            }
+        }
+        Ok(None)
+    }
+    /// Get the unique build ID from the `NT_GNU_BUILD_ID` note, if the file has one.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// # let mut data = [0u32; 256];
+    /// # let elf = include_bytes!("../examples/notes.elf");
+    /// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+    /// let elf = Elf::new(&data)?;
+    /// let build_id = elf.build_id()?.unwrap();
+    /// assert_eq!(build_id.len(), 20);
+    ///
+    /// let program = elf.programs()?.flatten().find(|program| program.header.ty == ProgramType::Note).unwrap();
+    /// for note in NoteIter::from_program(program)? {
+    ///     match note?.gnu()? {
+    ///         Some(GnuNote::AbiTag(tag)) => assert_eq!((tag.os, tag.major, tag.minor), (AbiOs::Linux, 6, 1)),
+    ///         Some(GnuNote::BuildId(id)) => assert_eq!(id, build_id),
+    ///         Some(GnuNote::Properties(properties)) => {
+    ///             let property = properties.into_iter().next().unwrap()?;
+    ///             assert_eq!(property.ty, GnuPropertyType::RiscVFeature1And);
+    ///             assert_eq!(property.as_integer()?, 3);
+    ///         }
+    ///         note => panic!("{note:?}")
+    ///     }
+    /// }
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn build_id(&self) -> Result<Option<&'a [u8]>> {
+        Ok(self.find_note(Note::GNU, GnuNoteType::BuildId.into())?.map(|note| note.desc))
+    }
+}
+
+/// An iterator over the notes in a `SHT_NOTE` section, `PT_NOTE` segment or any other buffer of notes.
+///
+/// A buffer of notes can also be read from a running image, e.g. between symbols placed around `.note.gnu.build-id` by
+/// the linker script.
+#[derive(Clone)]
+pub struct NoteIter<'a> {
+    data: &'a [u8],
+    align: usize
+}
+impl<'a> NoteIter<'a> {
+    /// Create an iterator over raw notes padded to `align` bytes.
+    ///
+    /// Notes are padded to 4 bytes unless `align` is 8, which is used by some ELF64 notes such as
+    /// `NT_GNU_PROPERTY_TYPE_0`. The start of `data` is treated as aligned.
+    pub fn new(data: &'a [u8], align: u64) -> Self {
+        Self {
+            data,
+            align: if align == 8 { 8 } else { 4 }
+        }
+    }
+    /// Coerce a `SHT_NOTE` section into an iterator over its notes.
+    pub fn from_section<C: Class>(section: Section<'a, C>) -> Result<Self> {
+        section.check_type(SectionType::Note)?;
+        Ok(Self::new(section.data, section.header.alignment().into()))
+    }
+    /// Coerce a `PT_NOTE` or `PT_GNU_PROPERTY` segment into an iterator over its notes.
+    pub fn from_program<C: Class>(program: Program<'a, C>) -> Result<Self> {
+        if program.header.ty() != ProgramType::GnuProperty {
+            program.check_type(ProgramType::Note)?;
+        }
+        Ok(Self::new(program.data, program.header.align().into()))
+    }
+    /// Find the first note with the given owner `name` and type.
+    pub fn find_note(self, name: &[u8], ty: u32) -> Result<Option<Note<'a>>> {
+        for note in self {
+            let note = note?;
+            if note.name == name && note.ty == ty {
+                return Ok(Some(note))
+            }
+        }
+        Ok(None)
+    }
+}
+impl<'a> Iterator for NoteIter<'a> {
+    type Item = Result<Note<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None
+        }
+        let result = (|| {
+            let header = NoteHeader::view(self.data)?;
+            let name_size = header.name_size.get() as usize;
+            let desc_size = header.desc_size.get() as usize;
+            let name_start = core::mem::size_of::<NoteHeader>();
+            let name_end = name_start.checked_add(name_size).ok_or(Error::IntegerOverflow)?;
+            let desc_start = align_up(name_end, self.align).ok_or(Error::IntegerOverflow)?;
+            let desc_end = desc_start.checked_add(desc_size).ok_or(Error::IntegerOverflow)?;
+            let name = self.data.get(name_start..name_end).ok_or(Error::UnexpectedEoF)?;
+            let desc = self.data.get(desc_start..desc_end).ok_or(Error::UnexpectedEoF)?;
+            // The last note may omit the padding after its descriptor
+            let end = align_up(desc_end, self.align).ok_or(Error::IntegerOverflow)?.min(self.data.len());
+            self.data = &self.data[end..];
+            Ok(Note {
+                name: name.strip_suffix(b"\0").unwrap_or(name),
+                ty: header.ty.get(),
+                desc,
+                align: self.align
+            })
+        })();
+        if result.is_err() {
+            self.data = &[];
+        }
+        Some(result)
+    }
+}
+impl<'a> fmt::Debug for NoteIter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NoteIter")
+            .field("data", &[..])
+            .field("align", &self.align)
+            .finish()
+    }
+}
+
+fn align_up(offset: usize, align: usize) -> Option<usize> {
+    Some(offset.checked_add(align - 1)? & !(align - 1))
+}
+
+view!{
+    /// The header of a note, identical in both classes.
+    #[derive(Debug)]
+    pub struct NoteHeader {
+        pub name_size: Le<u32>,
+        pub desc_size: Le<u32>,
+        pub ty: Le<u32>
+    }
+}
+
+/// A single note.
+///
+/// The meaning of `ty` depends on the owner `name`.
+#[derive(Clone, Copy)]
+pub struct Note<'a> {
+    /// The owner, without the null terminator.
+    pub name: &'a [u8],
+    pub ty: u32,
+    /// The descriptor, which starts at an offset aligned to 4 bytes or to the alignment of the notes.
+    pub desc: &'a [u8],
+    align: usize
+}
+impl<'a> Note<'a> {
+    /// The owner of notes defined by the GNU toolchain.
+    pub const GNU: &'static [u8] = b"GNU";
+
+    /// Decode a note owned by `"GNU"`, returning `None` for any other owner.
+    ///
+    /// Types without a decoded form, including those this crate does not know, are returned as `GnuNote::Other`.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// // A note of type 0x1234 owned by "GNU", with a 4 byte descriptor
+    /// let data = [4, 0, 0, 0, 4, 0, 0, 0, 0x34, 0x12, 0, 0, b'G', b'N', b'U', 0, 1, 2, 3, 4];
+    /// let note = NoteIter::new(&data, 4).next().unwrap()?;
+    /// assert!(matches!(note.gnu()?, Some(GnuNote::Other(0x1234, [1, 2, 3, 4]))));
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn gnu(&self) -> Result<Option<GnuNote<'a>>> {
+        if self.name != Self::GNU {
+            return Ok(None)
+        }
+        let note = match GnuNoteType(self.ty) {
+            GnuNoteType::AbiTag => {
+                let word = |index: u32| read_u32(self.desc, index);
+                GnuNote::AbiTag(AbiTag {
+                    os: AbiOs(word(0)?),
+                    major: word(1)?,
+                    minor: word(2)?,
+                    patch: word(3)?
+                })
+            }
+            GnuNoteType::BuildId => GnuNote::BuildId(self.desc),
+            GnuNoteType::PropertyType0 => GnuNote::Properties(GnuProperties { data: self.desc, align: self.align }),
+            _ => GnuNote::Other(self.ty, self.desc)
+        };
+        Ok(Some(note))
+    }
+}
+impl<'a> fmt::Debug for Note<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Note")
+            .field("name", &core::str::from_utf8(self.name).unwrap_or("<invalid>"))
+            .field("ty", &self.ty)
+            .field("desc", &[..])
+            .finish()
+    }
+}
+
+/// A decoded note owned by `"GNU"`.
+#[derive(Debug, Clone)]
+pub enum GnuNote<'a> {
+    /// `NT_GNU_ABI_TAG`, the minimum kernel version the file requires.
+    AbiTag(AbiTag),
+    /// `NT_GNU_BUILD_ID`, a unique identifier for the build, usually a 20 byte SHA-1 hash.
+    BuildId(&'a [u8]),
+    /// `NT_GNU_PROPERTY_TYPE_0`, the program properties used by the loader.
+    Properties(GnuProperties<'a>),
+    /// Any other type, such as `GnuNoteType::GoldVersion`, with its raw descriptor.
+    Other(u32, &'a [u8])
+}
+
+/// The descriptor of an `NT_GNU_ABI_TAG` note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbiTag {
+    pub os: AbiOs,
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32
+}
+
+/// An iterator over the properties in an `NT_GNU_PROPERTY_TYPE_0` note.
+#[derive(Clone)]
+pub struct GnuProperties<'a> {
+    data: &'a [u8],
+    align: usize
+}
+impl<'a> Iterator for GnuProperties<'a> {
+    type Item = Result<GnuProperty<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None
+        }
+        let result = (|| {
+            let header = <[Le<u32>; 2]>::view(self.data)?;
+            let size = header[1].get() as usize;
+            let end = size.checked_add(8).ok_or(Error::IntegerOverflow)?;
+            let data = self.data.get(8..end).ok_or(Error::UnexpectedEoF)?;
+            let end = align_up(end, self.align).ok_or(Error::IntegerOverflow)?.min(self.data.len());
+            self.data = &self.data[end..];
+            Ok(GnuProperty { ty: GnuPropertyType(header[0].get()), data })
+        })();
+        if result.is_err() {
+            self.data = &[];
+        }
+        Some(result)
+    }
+}
+impl<'a> fmt::Debug for GnuProperties<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GnuProperties")
+            .field("data", &[..])
+            .field("align", &self.align)
+            .finish()
+    }
+}
+
+/// A single program property.
+#[derive(Debug, Clone, Copy)]
+pub struct GnuProperty<'a> {
+    pub ty: GnuPropertyType,
+    pub data: &'a [u8]
+}
+impl<'a> GnuProperty<'a> {
+    /// Decode the data as a 4 or 8 byte little-endian integer, as used by the feature bitmasks and the stack size.
+    pub fn as_integer(&self) -> Result<u64> {
+        match *self.data {
+            [a, b, c, d] => Ok(u32::from_le_bytes([a, b, c, d]).into()),
+            [a, b, c, d, e, f, g, h] => Ok(u64::from_le_bytes([a, b, c, d, e, f, g, h])),
+            _ => Err(Error::InvalidEntrySize)
+        }
+    }
+}
+
+c_enum!{
+    pub GnuNoteType(u32) {
+        AbiTag = 1,
+        Hwcap = 2,
+        BuildId = 3,
+        GoldVersion = 4,
+        PropertyType0 = 5
+    } v => Err(Error::UnsupportedNoteType(Self(v)))
+}
+c_enum!{
+    pub AbiOs(u32) {
+        Linux = 0,
+        Hurd = 1,
+        Solaris = 2,
+        FreeBsd = 3
+    } v => Err(Error::UnsupportedAbiOs(Self(v)))
+}
+c_enum!{
+    pub GnuPropertyType(u32) {
+        StackSize = 1,
+        NoCopyOnProtected = 2,
+        RiscVFeature1And = 0xC0000000
+    } v => Err(Error::UnsupportedGnuPropertyType(Self(v)))
+}
@@ -0,0 +1,358 @@
+use core::{mem::size_of, fmt, marker::PhantomData};
+use crate::{
+    Address, Class, Elf, Elf32, Elf64, ElfSectionHeader, Error, Le, Offset, Result, Section, SectionType, SymbolTable,
+    TableIter, View
+};
+
+/// A view of a `SHT_REL` or `SHT_RELA` section, along with the symbol table it references and the section it patches.
+///
+/// In relocatable files `offset` is relative to the start of the target section, otherwise it is a virtual address.
+/// A table with `sh_link` of 0 has no symbol table, which is only valid if no entry refers to a symbol.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// # let mut data = [0u32; 8192];
+/// # let elf = include_bytes!("../examples/module.o");
+/// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+/// let elf = Elf::new(&data)?;
+/// for section in elf.sections()? {
+///     let section = section?;
+///     if section.header.ty != SectionType::Rela {
+///         continue
+///     }
+///     let table = RelaTable::new(&elf, section)?;
+///     let target = table.target.unwrap();
+///     for rela in table.relocations()? {
+///         let rela = rela?;
+///         let symbol = table.symbol(rela.symbol())?;
+///         println!(
+///             "{} patches {:X?} of {} against {} with {:?}",
+///             elf.section_name(&section)?,
+///             rela.offset,
+///             elf.section_name(&target)?,
+///             table.symbols.unwrap().name(symbol)?,
+///             rela.ty()
+///         )
+///     }
+/// }
+/// # Ok(()) })().unwrap()
+/// ```
+pub struct RelocationTable<'a, R: Relocation> {
+    data: &'a [u8],
+    entry_size: u16,
+    /// The symbol table named by `sh_link`, or `None` if `sh_link` is 0.
+    pub symbols: Option<SymbolTable<'a, R::Class>>,
+    /// The section named by `sh_info`, or `None` for tables such as `.rela.dyn` that are not tied to one section.
+    pub target: Option<Section<'a, R::Class>>,
+    /// The index of `target` in the section header table.
+    pub target_index: u32,
+    _marker: PhantomData<R>
+}
+/// A `SHT_REL` section.
+pub type RelTable<'a> = RelocationTable<'a, Rel>;
+/// A `SHT_RELA` section.
+pub type RelaTable<'a> = RelocationTable<'a, Rela>;
+/// A `SHT_REL` section in an ELF64 file.
+pub type RelTable64<'a> = RelocationTable<'a, Rel64>;
+/// A `SHT_RELA` section in an ELF64 file.
+pub type RelaTable64<'a> = RelocationTable<'a, Rela64>;
+impl<'a, R: 'a + Relocation> RelocationTable<'a, R> {
+    /// Coerce a section into a relocation table, following `sh_link` to the symbol table and `sh_info` to the section
+    /// the relocations apply to.
+    ///
+    /// The section must be of type `SHT_REL` for `Rel` or `SHT_RELA` for `Rela`.
+    ///
+    /// ```
+    /// use elf_riscv32::*;
+    /// # (|| -> Result<()> {
+    /// // Unlink `.rela.text` from the symbol table
+    /// let mut data = include_bytes!("../examples/module.o").to_vec();
+    /// let elf = Elf::from_bytes(&data)?;
+    /// let link = elf.header.sh_offset.get().as_usize()? + 3 * core::mem::size_of::<SectionHeader>() + 24;
+    /// data[link..link + 4].fill(0);
+    ///
+    /// let elf = Elf::from_bytes(&data)?;
+    /// let table = RelaTable::new(&elf, elf.section(3)?)?;
+    /// assert!(table.symbols.is_none());
+    /// let rela = table.relocations()?.next().unwrap()?;
+    /// assert!(matches!(table.symbol(rela.symbol()), Err(Error::MissingSymbolTable)));
+    /// # Ok(()) })().unwrap()
+    /// ```
+    pub fn new(elf: &Elf<'a, R::Class>, section: Section<'a, R::Class>) -> Result<Self> {
+        section.check_type(R::SECTION_TYPE)?;
+        let entry_size: u64 = section.header.entry_size().into();
+        let entry_size = entry_size.try_into().map_err(|_| Error::InvalidEntrySize)?;
+        if (entry_size as usize) < size_of::<R>() {
+            return Err(Error::InvalidEntrySize)
+        }
+        let symbols = match section.header.link() {
+            0 => None,
+            link => Some(SymbolTable::new(elf, elf.section(link)?)?)
+        };
+        let target_index = section.header.info();
+        let target = if target_index != 0 {
+            Some(elf.section(target_index)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            data: section.data,
+            entry_size,
+            symbols,
+            target,
+            target_index,
+            _marker: PhantomData
+        })
+    }
+    /// Returns the number of relocations in the table.
+    pub fn len(&self) -> u32 {
+        (self.data.len() / self.entry_size as usize) as u32
+    }
+    /// Returns true if the table has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Get the relocation at `index`.
+    pub fn get(&self, index: u32) -> Result<&'a R> {
+        if index >= self.len() {
+            return Err(Error::IndexOutOfRange)
+        }
+        R::view(&self.data[index as usize * self.entry_size as usize..])
+    }
+    /// Get the symbol at `index` in the linked symbol table.
+    ///
+    /// Returns `Error::MissingSymbolTable` if the table has none.
+    pub fn symbol(&self, index: u32) -> Result<&'a <R::Class as Class>::Symbol> {
+        self.symbols.ok_or(Error::MissingSymbolTable)?.get(index)
+    }
+    /// Get an iterator over relocations.
+    pub fn relocations(&self) -> Result<TableIter<'a, &'a R>> {
+        TableIter::new(self.data, Offset(0u32), self.len(), self.entry_size)
+    }
+}
+impl<'a, R: Relocation> fmt::Debug for RelocationTable<'a, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RelocationTable")
+            .field("data", &[..])
+            .field("entry_size", &self.entry_size)
+            .field("symbols", &self.symbols)
+            .field("target", &self.target)
+            .field("target_index", &self.target_index)
+            .finish()
+    }
+}
+
+/// Common accessors for `Rel` and `Rela` entries of either class.
+pub trait Relocation: View {
+    /// The class of file holding this kind of relocation.
+    type Class: Class;
+    /// The section type holding this kind of relocation.
+    const SECTION_TYPE: SectionType;
+    /// The location to patch.
+    fn offset(&self) -> Address<<Self::Class as Class>::Word>;
+    /// The raw `r_info` field.
+    fn info(&self) -> <Self::Class as Class>::Word;
+    /// The explicit addend, or `None` if it is stored at the location being patched.
+    fn addend(&self) -> Option<<Self::Class as Class>::Sword>;
+    /// The index of the symbol in the linked symbol table, from the upper 24 bits of `info` in ELF32 or the upper 32
+    /// bits in ELF64.
+    fn symbol(&self) -> u32 {
+        <Self::Class as Class>::split_info(self.info()).0
+    }
+    /// The relocation type, from the lower 8 bits of `info` in ELF32 or the lower 32 bits in ELF64.
+    fn ty(&self) -> RelocationType {
+        RelocationType(<Self::Class as Class>::split_info(self.info()).1)
+    }
+}
+
+view!{
+    /// A relocation entry without an explicit addend.
+    #[derive(Debug)]
+    pub struct Rel {
+        pub offset: Le<Address>,
+        pub info: Le<u32>
+    }
+}
+impl Rel {
+    pub fn new(entry: &[u8]) -> Result<&Self> {
+        Self::view(entry)
+    }
+}
+impl Relocation for Rel {
+    type Class = Elf32;
+    const SECTION_TYPE: SectionType = SectionType::Rel;
+    fn offset(&self) -> Address {
+        self.offset.get()
+    }
+    fn info(&self) -> u32 {
+        self.info.get()
+    }
+    fn addend(&self) -> Option<i32> {
+        None
+    }
+}
+
+view!{
+    /// A relocation entry with an explicit addend.
+    #[derive(Debug)]
+    pub struct Rela {
+        pub offset: Le<Address>,
+        pub info: Le<u32>,
+        pub addend: Le<i32>
+    }
+}
+impl Rela {
+    pub fn new(entry: &[u8]) -> Result<&Self> {
+        Self::view(entry)
+    }
+    /// Create a relocation of type `ty` at `offset` against the symbol at index `symbol`.
+    ///
+    /// `r_info` holds the symbol index in 24 bits, so returns `Error::IndexOutOfRange` for an index of `1 << 24` or
+    /// more.
+    pub fn from_parts(offset: u32, symbol: u32, ty: RelocationType, addend: i32) -> Result<Self> {
+        if symbol >= 1 << 24 {
+            return Err(Error::IndexOutOfRange)
+        }
+        Ok(Self {
+            offset: Address(offset).into(),
+            info: (symbol << 8 | ty.0 & 0xFF).into(),
+            addend: addend.into()
+        })
+    }
+}
+impl Relocation for Rela {
+    type Class = Elf32;
+    const SECTION_TYPE: SectionType = SectionType::Rela;
+    fn offset(&self) -> Address {
+        self.offset.get()
+    }
+    fn info(&self) -> u32 {
+        self.info.get()
+    }
+    fn addend(&self) -> Option<i32> {
+        Some(self.addend.get())
+    }
+}
+
+view!{
+    /// An ELF64 relocation entry without an explicit addend.
+    #[derive(Debug)]
+    pub struct Rel64 {
+        pub offset: Le<Address<u64>>,
+        pub info: Le<u64>
+    }
+}
+impl Rel64 {
+    pub fn new(entry: &[u8]) -> Result<&Self> {
+        Self::view(entry)
+    }
+}
+impl Relocation for Rel64 {
+    type Class = Elf64;
+    const SECTION_TYPE: SectionType = SectionType::Rel;
+    fn offset(&self) -> Address<u64> {
+        self.offset.get()
+    }
+    fn info(&self) -> u64 {
+        self.info.get()
+    }
+    fn addend(&self) -> Option<i64> {
+        None
+    }
+}
+
+view!{
+    /// An ELF64 relocation entry with an explicit addend.
+    #[derive(Debug)]
+    pub struct Rela64 {
+        pub offset: Le<Address<u64>>,
+        pub info: Le<u64>,
+        pub addend: Le<i64>
+    }
+}
+impl Rela64 {
+    pub fn new(entry: &[u8]) -> Result<&Self> {
+        Self::view(entry)
+    }
+}
+impl Relocation for Rela64 {
+    type Class = Elf64;
+    const SECTION_TYPE: SectionType = SectionType::Rela;
+    fn offset(&self) -> Address<u64> {
+        self.offset.get()
+    }
+    fn info(&self) -> u64 {
+        self.info.get()
+    }
+    fn addend(&self) -> Option<i64> {
+        Some(self.addend.get())
+    }
+}
+
+c_enum!{
+    pub RelocationType(u32) {
+        None = 0,
+        Abs32 = 1,
+        Abs64 = 2,
+        Relative = 3,
+        Copy = 4,
+        JumpSlot = 5,
+        TlsDtpMod32 = 6,
+        TlsDtpMod64 = 7,
+        TlsDtpRel32 = 8,
+        TlsDtpRel64 = 9,
+        TlsTpRel32 = 10,
+        TlsTpRel64 = 11,
+        TlsDesc = 12,
+        Branch = 16,
+        Jal = 17,
+        Call = 18,
+        CallPlt = 19,
+        GotHi20 = 20,
+        TlsGotHi20 = 21,
+        TlsGdHi20 = 22,
+        PcrelHi20 = 23,
+        PcrelLo12I = 24,
+        PcrelLo12S = 25,
+        Hi20 = 26,
+        Lo12I = 27,
+        Lo12S = 28,
+        TpRelHi20 = 29,
+        TpRelLo12I = 30,
+        TpRelLo12S = 31,
+        TpRelAdd = 32,
+        Add8 = 33,
+        Add16 = 34,
+        Add32 = 35,
+        Add64 = 36,
+        Sub8 = 37,
+        Sub16 = 38,
+        Sub32 = 39,
+        Sub64 = 40,
+        Got32Pcrel = 41,
+        Align = 43,
+        RvcBranch = 44,
+        RvcJump = 45,
+        RvcLui = 46,
+        GpRelI = 47,
+        GpRelS = 48,
+        TpRelI = 49,
+        TpRelS = 50,
+        Relax = 51,
+        Sub6 = 52,
+        Set6 = 53,
+        Set8 = 54,
+        Set16 = 55,
+        Set32 = 56,
+        Pcrel32 = 57,
+        IRelative = 58,
+        Plt32 = 59,
+        SetUleb128 = 60,
+        SubUleb128 = 61,
+        TlsDescHi20 = 62,
+        TlsDescLoadLo12 = 63,
+        TlsDescAddLo12 = 64,
+        TlsDescCall = 65
+    } v => Err(Error::UnsupportedRelocationType(Self(v)))
+}
@@ -0,0 +1,266 @@
+use core::{mem::size_of, fmt, marker::PhantomData};
+use crate::{
+    Address, Class, Elf, Elf32, Elf64, ElfSectionHeader, ElfSymbol, Error, Le, Offset, Result, Section, SectionType,
+    StringTable, TableIter, View
+};
+
+impl<'a, C: Class> Elf<'a, C> {
+    /// Get the static symbol table, usually `.symtab`, if the file has one.
+    pub fn symbol_table(&self) -> Result<Option<SymbolTable<'a, C>>> {
+        self.find_section(SectionType::SymbolTable)?
+            .map(|section| SymbolTable::new(self, section))
+            .transpose()
+    }
+    /// Get the dynamic symbol table, usually `.dynsym`, if the file has one.
+    pub fn dynamic_symbol_table(&self) -> Result<Option<SymbolTable<'a, C>>> {
+        self.find_section(SectionType::DynamicSymbolTable)?
+            .map(|section| SymbolTable::new(self, section))
+            .transpose()
+    }
+}
+
+/// A view of a `SHT_SYMTAB` or `SHT_DYNSYM` section and the string table holding its symbol names.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// # let mut data = [0u32; 8192];
+/// # let elf = include_bytes!("../examples/test.elf");
+/// # unsafe { (data.as_mut_ptr() as *mut u8).copy_from_nonoverlapping(elf.as_ptr(), elf.len()) };
+/// let elf = Elf::new(&data)?;
+/// let symbols = elf.symbol_table()?.unwrap();
+/// let (_, start) = symbols.find("_start")?.unwrap();
+/// assert_eq!(start.value.get().as_usize()?, elf.header.entry.get().as_usize()?);
+/// assert_eq!(start.binding(), SymbolBinding::Global);
+/// assert_eq!(start.section(), SymbolSection::Index(2));
+/// for symbol in symbols.symbols()? {
+///     let symbol = symbol?;
+///     println!("{} = {symbol:X?}", symbols.name(symbol)?)
+/// }
+/// # Ok(()) })().unwrap()
+/// ```
+#[derive(Clone, Copy)]
+pub struct SymbolTable<'a, C: Class = Elf32> {
+    data: &'a [u8],
+    entry_size: u16,
+    indices: Option<&'a [u8]>,
+    pub strings: StringTable<'a>,
+    _marker: PhantomData<C>
+}
+impl<'a, C: Class> SymbolTable<'a, C> {
+    /// Coerce a section into a symbol table, using `sh_link` to find the associated string table.
+    ///
+    /// The section must be of type `SHT_SYMTAB` or `SHT_DYNSYM`. Any `SHT_SYMTAB_SHNDX` section linked to it is used to
+    /// resolve section indices that do not fit in `Symbol::section_index`.
+    pub fn new(elf: &Elf<'a, C>, section: Section<'a, C>) -> Result<Self> {
+        let strings = StringTable::new(elf.section(section.header.link())?)?;
+        let mut table = Self::with_strings(section, strings)?;
+        for other in elf.sections()? {
+            let other = other?;
+            if other.header.ty() != SectionType::SymbolIndex {
+                continue
+            }
+            if core::ptr::eq(elf.section(other.header.link())?.header, section.header) {
+                table = table.with_indices(other)?;
+                break
+            }
+        }
+        Ok(table)
+    }
+    /// Like `SymbolTable::new` but uses the given string table rather than following `sh_link`.
+    pub fn with_strings(section: Section<'a, C>, strings: StringTable<'a>) -> Result<Self> {
+        if section.header.ty() != SectionType::DynamicSymbolTable {
+            section.check_type(SectionType::SymbolTable)?;
+        }
+        Self::from_data(section.data, section.header.entry_size(), strings)
+    }
+    /// Create a symbol table from raw entries, such as those found through the dynamic section.
+    pub fn from_data(data: &'a [u8], entry_size: C::Word, strings: StringTable<'a>) -> Result<Self> {
+        let entry_size: u64 = entry_size.into();
+        let entry_size = entry_size.try_into().map_err(|_| Error::InvalidEntrySize)?;
+        if (entry_size as usize) < size_of::<C::Symbol>() {
+            return Err(Error::InvalidEntrySize)
+        }
+        Ok(Self {
+            data,
+            entry_size,
+            indices: None,
+            strings,
+            _marker: PhantomData
+        })
+    }
+    /// Use the `SHT_SYMTAB_SHNDX` section `indices` to resolve extended section indices.
+    pub fn with_indices(self, indices: Section<'a, C>) -> Result<Self> {
+        indices.check_type(SectionType::SymbolIndex)?;
+        Ok(Self {
+            indices: Some(indices.data),
+            ..self
+        })
+    }
+    /// Returns the number of symbols in the table, including the null symbol at index 0.
+    pub fn len(&self) -> u32 {
+        (self.data.len() / self.entry_size as usize) as u32
+    }
+    /// Returns true if the table has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Get the symbol at `index`.
+    pub fn get(&self, index: u32) -> Result<&'a C::Symbol> {
+        if index >= self.len() {
+            return Err(Error::IndexOutOfRange)
+        }
+        C::Symbol::view(&self.data[index as usize * self.entry_size as usize..])
+    }
+    /// Get an iterator over symbols.
+    pub fn symbols(&self) -> Result<TableIter<'a, &'a C::Symbol>> {
+        TableIter::new(self.data, Offset(0u32), self.len(), self.entry_size)
+    }
+    /// Get the section the symbol at `index` is defined relative to.
+    ///
+    /// Unlike `Symbol::section`, a section index held in the `SHT_SYMTAB_SHNDX` section is resolved.
+    pub fn section(&self, index: u32) -> Result<SymbolSection> {
+        match self.get(index)?.section() {
+            SymbolSection::Extended => {
+                let indices = self.indices.ok_or(Error::UnsupportedSymbolSection(SymbolSection::Extended))?;
+                let start = (index as usize).checked_mul(4).ok_or(Error::IntegerOverflow)?;
+                let entry = indices.get(start..).and_then(|entry| entry.get(..4)).ok_or(Error::UnexpectedEoF)?;
+                Ok(SymbolSection::Index(u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]])))
+            }
+            section => Ok(section)
+        }
+    }
+    /// Get the name of a symbol from the linked string table.
+    pub fn name(&self, symbol: &C::Symbol) -> Result<&'a str> {
+        self.strings.get_str(symbol.name())
+    }
+    /// Find the first symbol with the given name by scanning the table, returning it with its index.
+    pub fn find(&self, name: &str) -> Result<Option<(u32, &'a C::Symbol)>> {
+        for (index, symbol) in self.symbols()?.enumerate() {
+            let symbol = symbol?;
+            if symbol.name() != 0 && self.strings.get_bytes(symbol.name())? == name.as_bytes() {
+                return Ok(Some((index as u32, symbol)))
+            }
+        }
+        Ok(None)
+    }
+}
+impl<'a, C: Class> fmt::Debug for SymbolTable<'a, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SymbolTable")
+            .field("data", &[..])
+            .field("entry_size", &self.entry_size)
+            .field("strings", &self.strings)
+            .finish()
+    }
+}
+
+view!{
+    /// A symbol table entry.
+    #[derive(Debug)]
+    pub struct Symbol {
+        pub name: Le<u32>,
+        pub value: Le<Address>,
+        pub size: Le<u32>,
+        pub info: u8,
+        pub other: u8,
+        pub section_index: Le<u16>
+    }
+}
+impl Symbol {
+    pub fn new(entry: &[u8]) -> Result<&Self> {
+        Self::view(entry)
+    }
+}
+impl ElfSymbol<Elf32> for Symbol {
+    fn info(&self) -> u8 {
+        self.info
+    }
+    fn other(&self) -> u8 {
+        self.other
+    }
+    fields!(name: u32, value: Address, size: u32, section_index: u16);
+}
+
+view!{
+    /// An ELF64 symbol table entry, which moves `value` and `size` to the end to keep them aligned.
+    #[derive(Debug)]
+    pub struct Symbol64 {
+        pub name: Le<u32>,
+        pub info: u8,
+        pub other: u8,
+        pub section_index: Le<u16>,
+        pub value: Le<Address<u64>>,
+        pub size: Le<u64>
+    }
+}
+impl Symbol64 {
+    pub fn new(entry: &[u8]) -> Result<&Self> {
+        Self::view(entry)
+    }
+}
+impl ElfSymbol<Elf64> for Symbol64 {
+    fn info(&self) -> u8 {
+        self.info
+    }
+    fn other(&self) -> u8 {
+        self.other
+    }
+    fields!(name: u32, value: Address<u64>, size: u64, section_index: u16);
+}
+
+/// The decoded `st_shndx` of a symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolSection {
+    /// `SHN_UNDEF`, the symbol is defined elsewhere.
+    Undefined,
+    /// `SHN_ABS`, the value is absolute and unaffected by relocation.
+    Absolute,
+    /// `SHN_COMMON`, an unallocated common block.
+    Common,
+    /// `SHN_XINDEX`, the index is held in an associated `SHT_SYMTAB_SHNDX` section.
+    Extended,
+    /// Any other index in the reserved range.
+    Reserved(u16),
+    /// An index into the section header table.
+    Index(u32)
+}
+impl From<u16> for SymbolSection {
+    fn from(index: u16) -> Self {
+        match index {
+            0 => Self::Undefined,
+            0xFFF1 => Self::Absolute,
+            0xFFF2 => Self::Common,
+            0xFFFF => Self::Extended,
+            0xFF00..=0xFFFF => Self::Reserved(index),
+            index => Self::Index(index.into())
+        }
+    }
+}
+
+c_enum!{
+    pub SymbolBinding(u8) {
+        Local = 0,
+        Global = 1,
+        Weak = 2
+    } v => Err(Error::UnsupportedSymbolBinding(Self(v)))
+}
+c_enum!{
+    pub SymbolType(u8) {
+        NoType = 0,
+        Object = 1,
+        Function = 2,
+        Section = 3,
+        File = 4,
+        Common = 5,
+        ThreadLocalStorage = 6
+    } v => Err(Error::UnsupportedSymbolType(Self(v)))
+}
+c_enum!{
+    pub SymbolVisibility(u8) {
+        Default = 0,
+        Internal = 1,
+        Hidden = 2,
+        Protected = 3
+    } v => Err(Error::UnsupportedSymbolVisibility(Self(v)))
+}
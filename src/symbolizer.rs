@@ -0,0 +1,209 @@
+use core::{cmp::Reverse, fmt};
+use crate::{Class, Elf32, ElfSymbol, Error, Result, SymbolBinding, SymbolSection, SymbolTable, SymbolType};
+
+/// Maps addresses to the symbol holding them, e.g. to print a backtrace as `symbol+offset` without host tooling.
+///
+/// An address belongs to a symbol if it lies within `st_value..st_value + st_size`, preferring `STT_FUNC` symbols
+/// where several overlap. Failing that, a symbol with no size, such as a label in assembly, holds the addresses up to
+/// the next symbol. Section and file symbols, undefined and absolute symbols, and local labels such as `.L0` or the
+/// `$x` mapping symbols are never used.
+///
+/// `Symbolizer::new` scans the whole table on each lookup. `Symbolizer::with_index` sorts the symbols by address
+/// into a caller-provided buffer once so that lookups use a binary search, and `with_sorted_index` reuses such a buffer
+/// saved earlier.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// let elf = Elf::from_bytes(include_bytes!("../examples/libdynamic.so"))?;
+/// let symbols = elf.symbol_table()?.unwrap();
+/// let mut index = [0; 16];
+/// let sorted = Symbolizer::with_index(symbols, &mut index)?;
+/// let scan = Symbolizer::new(symbols);
+/// for (address, expected) in [
+///     (0x1233, Some("add+0x1")),
+///     (0x1240, Some("greet+0xa")),
+///     // `init` is a label with no size, so extends to the next symbol
+///     (0x1258, Some("init+0x8")),
+///     (0x3342, Some("version+0x2")),
+///     (0x3344, None),
+///     (0x100, None)
+/// ] {
+///     let found = sorted.symbolize(address)?;
+///     assert_eq!(found.map(|found| format!("{found}")).as_deref(), expected);
+///     assert_eq!(scan.symbolize(address)?.map(|found| found.index), found.map(|found| found.index));
+/// }
+/// # Ok(()) })().unwrap()
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Symbolizer<'a, 'i, C: Class = Elf32> {
+    symbols: SymbolTable<'a, C>,
+    /// The indices of the usable symbols sorted by address, with the largest symbol size, if sorted.
+    index: Option<(&'i [u32], u64)>
+}
+impl<'a, 'i, C: Class> Symbolizer<'a, 'i, C> {
+    /// Create a symbolizer that scans `symbols` on each lookup.
+    pub fn new(symbols: SymbolTable<'a, C>) -> Self {
+        Self {
+            symbols,
+            index: None
+        }
+    }
+    /// Create a symbolizer that sorts the usable symbols of `symbols` into `index` for binary search.
+    ///
+    /// `index` needs at most `symbols.len()` entries, and returns `Error::BufferTooSmall` if the usable symbols do not
+    /// fit.
+    pub fn with_index(symbols: SymbolTable<'a, C>, index: &'i mut [u32]) -> Result<Self> {
+        let mut count = 0;
+        for (position, symbol) in symbols.symbols()?.enumerate() {
+            if is_usable(&symbols, symbol?)? {
+                *index.get_mut(count).ok_or(Error::BufferTooSmall)? = position as u32;
+                count += 1;
+            }
+        }
+        let index = &mut index[..count];
+        index.sort_unstable_by_key(|&position| (value(&symbols, position), position));
+        Self::with_sorted_index(symbols, index)
+    }
+    /// Create a symbolizer from an `index` already sorted by `with_index`, such as one saved with the image.
+    pub fn with_sorted_index(symbols: SymbolTable<'a, C>, index: &'i [u32]) -> Result<Self> {
+        let mut max_size = 0;
+        for &position in index {
+            max_size = max_size.max(symbols.get(position)?.size().into());
+        }
+        Ok(Self {
+            symbols,
+            index: Some((index, max_size))
+        })
+    }
+    /// The sorted indices of the usable symbols, if built with `with_index` or `with_sorted_index`.
+    pub fn index(&self) -> Option<&'i [u32]> {
+        self.index.map(|(index, _)| index)
+    }
+    /// Find the symbol holding `address`, returning it with the offset of `address` into it.
+    pub fn symbolize(&self, address: C::Word) -> Result<Option<Symbolized<'a, C>>> {
+        let address: u64 = address.into();
+        let mut best = Best::<C>::new(address);
+        match self.index {
+            None => {
+                for (position, symbol) in self.symbols.symbols()?.enumerate() {
+                    let symbol = symbol?;
+                    if is_usable(&self.symbols, symbol)? {
+                        best.add(position as u32, symbol);
+                    }
+                }
+            }
+            Some((index, max_size)) => {
+                let end = index.partition_point(|&position| value(&self.symbols, position) <= address);
+                // Walk back through every symbol at the nearest address, then those close enough to still hold it
+                let mut nearest = None;
+                for &position in index[..end].iter().rev() {
+                    let symbol = self.symbols.get(position)?;
+                    let value: u64 = symbol.value().0.into();
+                    if value != *nearest.get_or_insert(value) && address - value >= max_size {
+                        break
+                    }
+                    best.add(position, symbol);
+                }
+            }
+        }
+        let Some((index, symbol)) = best.finish() else { return Ok(None) };
+        let start: u64 = symbol.value().0.into();
+        Ok(Some(Symbolized {
+            name: self.symbols.name(symbol)?,
+            index,
+            symbol,
+            offset: (address - start).try_into().map_err(|_| Error::IntegerOverflow)?
+        }))
+    }
+}
+
+/// An address resolved by `Symbolizer::symbolize`, displayed as `name+0x1C`.
+#[derive(Debug)]
+pub struct Symbolized<'a, C: Class = Elf32> {
+    pub name: &'a str,
+    /// The index of the symbol in its table.
+    pub index: u32,
+    pub symbol: &'a C::Symbol,
+    /// The distance from the start of the symbol.
+    pub offset: C::Word
+}
+impl<'a, C: Class> Clone for Symbolized<'a, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<'a, C: Class> Copy for Symbolized<'a, C> {}
+impl<'a, C: Class> fmt::Display for Symbolized<'a, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}+{:#x}", self.name, self.offset)
+    }
+}
+
+/// The best symbol seen so far for an address, following the rules given on `Symbolizer`.
+struct Best<'a, C: Class> {
+    address: u64,
+    /// The best symbol holding the address.
+    containing: Option<(u32, &'a C::Symbol)>,
+    /// The value of the nearest symbol at or below the address.
+    nearest: Option<u64>,
+    /// The best symbol with no size at or below the address.
+    label: Option<(u32, &'a C::Symbol)>
+}
+impl<'a, C: Class> Best<'a, C> {
+    fn new(address: u64) -> Self {
+        Self {
+            address,
+            containing: None,
+            nearest: None,
+            label: None
+        }
+    }
+    fn add(&mut self, index: u32, symbol: &'a C::Symbol) {
+        let value: u64 = symbol.value().0.into();
+        let size: u64 = symbol.size().into();
+        if value > self.address {
+            return
+        }
+        self.nearest = self.nearest.max(Some(value));
+        // Prefer functions, then the innermost symbol, then global symbols, then the first in the table. Labels are
+        // ranked by address first as only the nearest can be used.
+        let rank = |(index, symbol): (u32, &C::Symbol), label: bool| {
+            let value: u64 = symbol.value().0.into();
+            let function = symbol.ty() == SymbolType::Function;
+            let (first, second) = if label { (value, u64::from(function)) } else { (u64::from(function), value) };
+            (first, second, symbol.binding() != SymbolBinding::Local, Reverse(index))
+        };
+        let (slot, label) = if self.address - value < size {
+            (&mut self.containing, false)
+        } else if size == 0 {
+            (&mut self.label, true)
+        } else {
+            return
+        };
+        if slot.is_none_or(|best| rank((index, symbol), label) > rank(best, label)) {
+            *slot = Some((index, symbol));
+        }
+    }
+    fn finish(self) -> Option<(u32, &'a C::Symbol)> {
+        // A label only extends as far as the next symbol
+        self.containing.or(self.label.filter(|(_, label)| Some(label.value().0.into()) == self.nearest))
+    }
+}
+
+/// Returns true if `symbol` can name an address.
+fn is_usable<C: Class>(symbols: &SymbolTable<'_, C>, symbol: &C::Symbol) -> Result<bool> {
+    if symbol.name() == 0 || !matches!(symbol.ty(), SymbolType::NoType | SymbolType::Object | SymbolType::Function) {
+        return Ok(false)
+    }
+    if !matches!(symbol.section(), SymbolSection::Index(_) | SymbolSection::Extended) {
+        return Ok(false)
+    }
+    let name = symbols.strings.get_bytes(symbol.name())?;
+    Ok(!name.starts_with(b".L") && !name.starts_with(b"$"))
+}
+
+/// The value of the symbol at `index`, sorting missing symbols last.
+fn value<C: Class>(symbols: &SymbolTable<'_, C>, index: u32) -> u64 {
+    symbols.get(index).map_or(u64::MAX, |symbol| symbol.value().0.into())
+}
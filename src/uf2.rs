@@ -0,0 +1,207 @@
+use core::{fmt, mem::size_of};
+use crate::{Elf, Error, Exporter, ImageAddress, Le, Result, Sink, View};
+
+/// Converts the `PT_LOAD` segments of an executable into UF2 blocks for drag-and-drop flashing over USB.
+///
+/// The image is split into 256 byte payloads aligned to 256 bytes. A segment that starts or ends part way through a
+/// payload shares it with any neighbouring segment, and the rest is filled with `fill`.
+///
+/// ```
+/// use elf_riscv32::*;
+/// # (|| -> Result<()> {
+/// let elf = Elf::from_bytes(include_bytes!("../examples/test.elf"))?;
+/// let writer = Uf2Writer::new(&elf, ImageAddress::Load, Some(Uf2Family::Rp2350RiscV.into()), 0xFF);
+/// let mut buffer = [0; 1024];
+/// let mut sink = SliceSink::new(&mut buffer);
+/// writer.write(&mut sink)?;
+/// assert_eq!(writer.block_count()?, 2);
+///
+/// // Compare against the flat image of the segments
+/// let mut image = [0; 0x2000];
+/// let mut flat = SliceSink::new(&mut image);
+/// Exporter::new(&elf, ImageAddress::Load, Some(0xFF)).write_binary(&mut flat)?;
+/// let image = flat.written();
+/// for (number, block) in Uf2Blocks::new(sink.written()).enumerate() {
+///     let block = block?;
+///     assert_eq!((block.block_number.get(), block.block_count.get()), (number as u32, 2));
+///     assert_eq!(block.family(), Some(Uf2Family::Rp2350RiscV.into()));
+///     let offset = (block.target_address.get() - 0x10000) as usize;
+///     let payload = block.payload()?;
+///     let expected = &image[offset..image.len().min(offset + 256)];
+///     assert_eq!(&payload[..expected.len()], expected);
+///     assert!(payload[expected.len()..].iter().all(|&byte| byte == 0xFF));
+/// }
+/// # Ok(()) })().unwrap()
+/// ```
+#[derive(Debug)]
+pub struct Uf2Writer<'a, 'e> {
+    exporter: Exporter<'a, 'e>,
+    family: Option<u32>,
+    fill: u8
+}
+impl<'a, 'e> Uf2Writer<'a, 'e> {
+    /// Create a writer placing segments at `address`, tagging every block with the `family` ID if given.
+    pub fn new(elf: &'e Elf<'a>, address: ImageAddress, family: Option<u32>, fill: u8) -> Self {
+        Self {
+            exporter: Exporter::new(elf, address, None),
+            family,
+            fill
+        }
+    }
+    /// Returns the number of blocks in the image.
+    pub fn block_count(&self) -> Result<u32> {
+        let mut count = 0;
+        self.pages(|_, _| {
+            count += 1;
+            Ok(())
+        })?;
+        Ok(count)
+    }
+    /// Returns the size of the image in bytes.
+    pub fn size(&self) -> Result<usize> {
+        Ok(self.block_count()? as usize * size_of::<Uf2Block>())
+    }
+    /// Write the image to `sink`, returning its size in bytes.
+    pub fn write(&self, sink: &mut impl Sink) -> Result<usize> {
+        let block_count = self.block_count()?;
+        let mut block_number = 0;
+        self.pages(|address, payload| {
+            let mut data = [0; 476];
+            data[..payload.len()].copy_from_slice(payload);
+            let block = Uf2Block {
+                magic_start: [Uf2Block::MAGIC_START[0].into(), Uf2Block::MAGIC_START[1].into()],
+                flags: if self.family.is_some() { Uf2Flags::FamilyIdPresent } else { Uf2Flags::None }.into(),
+                target_address: address.into(),
+                payload_size: (payload.len() as u32).into(),
+                block_number: block_number.into(),
+                block_count: block_count.into(),
+                family_or_size: self.family.unwrap_or(0).into(),
+                data,
+                magic_end: Uf2Block::MAGIC_END.into()
+            };
+            block_number += 1;
+            sink.write(block.as_bytes())
+        })?;
+        Ok(block_count as usize * size_of::<Uf2Block>())
+    }
+
+    /// Call `page` with the address and contents of each aligned payload holding segment data.
+    fn pages(&self, mut page: impl FnMut(u32, &[u8; Uf2Block::PAYLOAD_SIZE]) -> Result<()>) -> Result<()> {
+        const MASK: u32 = Uf2Block::PAYLOAD_SIZE as u32 - 1;
+        let mut current: Option<(u32, [u8; Uf2Block::PAYLOAD_SIZE])> = None;
+        self.exporter.blocks(None, |address, mut data| {
+            let mut address = address;
+            while !data.is_empty() {
+                let start = address & !MASK;
+                if let Some((previous, payload)) = current.as_ref().filter(|(previous, _)| *previous != start) {
+                    page(*previous, payload)?;
+                    current = None;
+                }
+                let (_, payload) = current.get_or_insert((start, [self.fill; Uf2Block::PAYLOAD_SIZE]));
+                let offset = (address & MASK) as usize;
+                let size = data.len().min(payload.len() - offset);
+                payload[offset..][..size].copy_from_slice(&data[..size]);
+                data = &data[size..];
+                address = address.wrapping_add(size as u32);
+            }
+            Ok(())
+        })?;
+        match current {
+            Some((address, payload)) => page(address, &payload),
+            None => Ok(())
+        }
+    }
+}
+
+/// An iterator over the blocks of a UF2 image.
+#[derive(Clone)]
+pub struct Uf2Blocks<'a> {
+    data: &'a [u8]
+}
+impl<'a> Uf2Blocks<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data
+        }
+    }
+}
+impl<'a> Iterator for Uf2Blocks<'a> {
+    type Item = Result<&'a Uf2Block>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None
+        }
+        let result = Uf2Block::new(self.data);
+        self.data = self.data.get(size_of::<Uf2Block>()..).unwrap_or(&[]);
+        if result.is_err() {
+            self.data = &[];
+        }
+        Some(result)
+    }
+}
+impl<'a> fmt::Debug for Uf2Blocks<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Uf2Blocks")
+            .field("data", &[..])
+            .finish()
+    }
+}
+
+view!{
+    /// A 512 byte UF2 block, holding a payload of up to 476 bytes to be written at `target_address`.
+    #[derive(Debug)]
+    pub struct Uf2Block {
+        pub magic_start: [Le<u32>; 2],
+        pub flags: Le<Uf2Flags>,
+        pub target_address: Le<u32>,
+        pub payload_size: Le<u32>,
+        pub block_number: Le<u32>,
+        pub block_count: Le<u32>,
+        /// The family ID if `Uf2Flags::FamilyIdPresent` is set, otherwise the file size or 0.
+        pub family_or_size: Le<u32>,
+        pub data: [u8; 476],
+        pub magic_end: Le<u32>
+    }
+}
+impl Uf2Block {
+    pub const MAGIC_START: [u32; 2] = [0x0A324655, 0x9E5D5157];
+    pub const MAGIC_END: u32 = 0x0AB16F30;
+    /// The payload size used by most bootloaders, and by `Uf2Writer`.
+    pub const PAYLOAD_SIZE: usize = 256;
+
+    /// View a block, checking its magic numbers.
+    pub fn new(block: &[u8]) -> Result<&Self> {
+        let block = Self::view(block)?;
+        if block.magic_start != Self::MAGIC_START.map(Le::new) || block.magic_end != Self::MAGIC_END {
+            return Err(Error::InvalidMagic)
+        }
+        Ok(block)
+    }
+    /// The bytes to be written at `target_address`.
+    pub fn payload(&self) -> Result<&[u8]> {
+        self.data.get(..self.payload_size.get() as usize).ok_or(Error::InvalidEntrySize)
+    }
+    /// The board family the block is for, if tagged.
+    pub fn family(&self) -> Option<u32> {
+        self.flags.get().all(Uf2Flags::FamilyIdPresent).then(|| self.family_or_size.get())
+    }
+}
+
+c_flags!{
+    pub Uf2Flags(u32) {
+        NotMainFlash = 0x1,
+        FileContainer = 0x1000,
+        FamilyIdPresent = 0x2000,
+        Md5ChecksumPresent = 0x4000,
+        ExtensionTagsPresent = 0x8000
+    } v => Err(Error::UnsupportedUf2Flags(v))
+}
+c_enum!{
+    pub Uf2Family(u32) {
+        Rp2350RiscV = 0xE48BFF5A,
+        Esp32C2 = 0x2B88D29C,
+        Esp32C3 = 0xD42BA06C,
+        Esp32C6 = 0x540DDF62,
+        Esp32H2 = 0x332726F6
+    } v => Err(Error::UnsupportedUf2Family(Self(v)))
+}
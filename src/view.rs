@@ -0,0 +1,136 @@
+//! In-place views of the little-endian structures stored in an ELF file.
+//!
+//! Every on-disk structure is built from fields that are stored as little-endian bytes, so it has an alignment of 1,
+//! no padding, and no invalid bit patterns. It can be borrowed from any byte slice that is long enough and reads the
+//! same values regardless of the host's endianness.
+
+use core::{fmt, mem::{align_of, size_of}};
+use crate::{Error, Result};
+
+/// A type that can be borrowed in place from a byte slice.
+///
+/// # Safety
+/// Implementors must have an alignment of 1, no padding, and accept any bit pattern. Use the `view!` macro to
+/// declare a `repr(C)` structure whose fields are checked to all be `View`.
+pub unsafe trait View: Sized {
+    /// Borrow the start of `bytes` as `Self`.
+    fn view(bytes: &[u8]) -> Result<&Self> {
+        if bytes.len() < size_of::<Self>() {
+            return Err(Error::UnexpectedEoF)
+        }
+        if bytes.as_ptr().align_offset(align_of::<Self>()) != 0 {
+            return Err(Error::Unaligned)
+        }
+        // The length and alignment are checked above and the implementor guarantees any bit pattern is valid
+        Ok(unsafe { &*bytes.as_ptr().cast::<Self>() })
+    }
+    /// The bytes `self` is stored as, for writing it to a file.
+    fn as_bytes(&self) -> &[u8] {
+        // The implementor guarantees there is no padding, so every byte is initialised
+        unsafe { core::slice::from_raw_parts((self as *const Self).cast::<u8>(), size_of::<Self>()) }
+    }
+}
+unsafe impl View for u8 {}
+unsafe impl<T: View, const N: usize> View for [T; N] {}
+
+/// Reinterpret a word-aligned buffer as the bytes it holds in memory.
+pub(crate) fn words_as_bytes(words: &[u32]) -> &[u8] {
+    // `u8` has no alignment requirement and every byte of a `u32` is initialised
+    unsafe { core::slice::from_raw_parts(words.as_ptr().cast::<u8>(), core::mem::size_of_val(words)) }
+}
+
+/// Declare a `repr(C)` structure and implement `View` for it, checking at compile time that every field is `View`.
+macro_rules! view {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $field_ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(C)]
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $field_ty),*
+        }
+        // Every field is `View` so has an alignment of 1, leaving no room for padding
+        unsafe impl $crate::View for $name {}
+        const _: () = {
+            const fn is_view<T: $crate::View>() {}
+            $(is_view::<$field_ty>();)*
+            assert!(::core::mem::align_of::<$name>() == 1);
+        };
+    };
+}
+
+/// A value with a fixed-size little-endian encoding.
+pub trait Endian: Copy {
+    /// The encoded form, a byte array.
+    type Bytes: View + Copy;
+    fn from_le_bytes(bytes: Self::Bytes) -> Self;
+    fn to_le_bytes(self) -> Self::Bytes;
+}
+macro_rules! endian {
+    ($($ty:ty),*) => {
+        $(impl Endian for $ty {
+            type Bytes = [u8; size_of::<$ty>()];
+            fn from_le_bytes(bytes: Self::Bytes) -> Self {
+                <$ty>::from_le_bytes(bytes)
+            }
+            fn to_le_bytes(self) -> Self::Bytes {
+                <$ty>::to_le_bytes(self)
+            }
+        })*
+    };
+}
+endian!(u8, u16, u32, u64, i32, i64);
+
+/// A `T` stored as little-endian bytes in a file.
+///
+/// Compares equal to the `T` it holds, so fields can be checked directly, e.g. `header.ty == FileType::Executable`.
+#[repr(transparent)]
+pub struct Le<T: Endian>(T::Bytes);
+unsafe impl<T: Endian> View for Le<T> {}
+impl<T: Endian> Le<T> {
+    pub fn new(value: T) -> Self {
+        Self(value.to_le_bytes())
+    }
+    /// Decode the value.
+    pub fn get(&self) -> T {
+        T::from_le_bytes(self.0)
+    }
+    pub fn set(&mut self, value: T) {
+        self.0 = value.to_le_bytes()
+    }
+}
+impl<T: Endian> Clone for Le<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: Endian> Copy for Le<T> {}
+impl<T: Endian> From<T> for Le<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+impl<T: Endian + PartialEq> PartialEq for Le<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+impl<T: Endian + Eq> Eq for Le<T> {}
+impl<T: Endian + PartialEq> PartialEq<T> for Le<T> {
+    fn eq(&self, other: &T) -> bool {
+        self.get() == *other
+    }
+}
+impl<T: Endian + PartialOrd> PartialOrd<T> for Le<T> {
+    fn partial_cmp(&self, other: &T) -> Option<core::cmp::Ordering> {
+        self.get().partial_cmp(other)
+    }
+}
+impl<T: Endian + fmt::Debug> fmt::Debug for Le<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{Error, Result};
+
+/// A destination that a file is written to in order, such as a buffer, a block device or a serial port.
+pub trait Sink {
+    /// Append `data`.
+    fn write(&mut self, data: &[u8]) -> Result<()>;
+    /// Append `len` zero bytes of padding.
+    fn zeros(&mut self, len: usize) -> Result<()> {
+        let chunk = [0; 64];
+        let mut done = 0;
+        while done < len {
+            let size = (len - done).min(chunk.len());
+            self.write(&chunk[..size])?;
+            done += size;
+        }
+        Ok(())
+    }
+}
+
+/// A sink that fills a byte slice from the start.
+#[derive(Debug)]
+pub struct SliceSink<'b> {
+    data: &'b mut [u8],
+    len: usize
+}
+impl<'b> SliceSink<'b> {
+    pub fn new(data: &'b mut [u8]) -> Self {
+        Self {
+            data,
+            len: 0
+        }
+    }
+    /// Returns the number of bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Returns true if nothing has been written.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// The bytes written so far.
+    pub fn written(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+    fn reserve(&mut self, len: usize) -> Result<&mut [u8]> {
+        let start = self.len;
+        let end = start.checked_add(len).ok_or(Error::IntegerOverflow)?;
+        let data = self.data.get_mut(start..end).ok_or(Error::BufferTooSmall)?;
+        self.len = end;
+        Ok(data)
+    }
+}
+impl<'b> Sink for SliceSink<'b> {
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.reserve(data.len())?.copy_from_slice(data);
+        Ok(())
+    }
+    fn zeros(&mut self, len: usize) -> Result<()> {
+        self.reserve(len)?.fill(0);
+        Ok(())
+    }
+}
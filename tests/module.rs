@@ -0,0 +1,182 @@
+//! Loads `examples/module.o` and `examples/tls.o` with `ModuleLoader` and decodes each instruction it patched, checking
+//! that every relocation type used by the modules reaches its target, and that relocations needing a GOT are rejected.
+
+use elf_riscv32::*;
+
+/// Extract bits `high..=low` of `word`, shifted down to bit 0.
+fn bits(word: u32, high: u32, low: u32) -> u32 {
+    (word >> low) & ((1 << (high - low + 1)) - 1)
+}
+
+/// Sign-extend the low `width` bits of `value`.
+fn sign_extend(value: u32, width: u32) -> i32 {
+    ((value << (32 - width)) as i32) >> (32 - width)
+}
+
+fn imm_i(insn: u32) -> i32 {
+    sign_extend(bits(insn, 31, 20), 12)
+}
+
+fn imm_s(insn: u32) -> i32 {
+    sign_extend(bits(insn, 31, 25) << 5 | bits(insn, 11, 7), 12)
+}
+
+fn imm_u(insn: u32) -> i32 {
+    (insn & 0xFFFF_F000) as i32
+}
+
+fn imm_b(insn: u32) -> i32 {
+    let imm = bits(insn, 31, 31) << 12 | bits(insn, 7, 7) << 11 | bits(insn, 30, 25) << 5 | bits(insn, 11, 8) << 1;
+    sign_extend(imm, 13)
+}
+
+fn imm_j(insn: u32) -> i32 {
+    let imm = bits(insn, 31, 31) << 20 | bits(insn, 19, 12) << 12 | bits(insn, 20, 20) << 11 | bits(insn, 30, 21) << 1;
+    sign_extend(imm, 21)
+}
+
+fn imm_cb(insn: u16) -> i32 {
+    let insn = insn.into();
+    let imm = bits(insn, 12, 12) << 8 | bits(insn, 6, 5) << 6 | bits(insn, 2, 2) << 5 | bits(insn, 11, 10) << 3
+        | bits(insn, 4, 3) << 1;
+    sign_extend(imm, 9)
+}
+
+fn imm_cj(insn: u16) -> i32 {
+    let insn = insn.into();
+    let imm = bits(insn, 12, 12) << 11 | bits(insn, 8, 8) << 10 | bits(insn, 10, 9) << 8 | bits(insn, 6, 6) << 7
+        | bits(insn, 7, 7) << 6 | bits(insn, 2, 2) << 5 | bits(insn, 11, 11) << 4 | bits(insn, 5, 3) << 1;
+    sign_extend(imm, 12)
+}
+
+fn offset(address: u32, imm: i32) -> u32 {
+    address.wrapping_add(imm as u32)
+}
+
+#[test]
+fn relocations() -> Result<()> {
+    let elf = Elf::from_bytes(include_bytes!("../examples/module.o"))?;
+    let mut buffer = [0u8; 256];
+    let mut memory = Region::new(0x8000_0000, &mut buffer);
+    let mut addresses = [None; 16];
+    let mut loader = ModuleLoader::new(&elf, &mut addresses)?;
+    let mut next = 0x8000_0000u32;
+    loader.place(|_, section| {
+        let align = section.header.alignment.get().max(1);
+        let address = next.div_ceil(align) * align;
+        next = address + section.header.size.get();
+        Ok(address)
+    })?;
+    loader.load(&mut memory)?;
+    loader.relocate(&mut memory, |name| match name {
+        "print" => Some(0x8000_1000),
+        _ => None
+    })?;
+
+    let text = loader.symbol("module_init")?.unwrap();
+    let message = loader.symbol("message")?.unwrap();
+    let counter = loader.symbol("counter")?.unwrap();
+    let helper = loader.symbol("helper")?.unwrap();
+    assert_eq!(helper, text + 0x2E);
+    // Compressed instructions are the low half of the word read at their address
+    let mut word = |offset: u32| memory.read_u32(text + offset);
+
+    // `la a0, message`: R_RISCV_PCREL_HI20 and R_RISCV_PCREL_LO12_I
+    let (auipc, addi) = (word(0x04)?, word(0x08)?);
+    assert_eq!(offset(offset(text + 0x04, imm_u(auipc)), imm_i(addi)), message);
+    // `call print`: R_RISCV_CALL over auipc and jalr
+    let (auipc, jalr) = (word(0x0C)?, word(0x10)?);
+    assert_eq!(offset(offset(text + 0x0C, imm_u(auipc)), imm_i(jalr)), 0x8000_1000);
+    // `lui` with `lw` and `sw`: R_RISCV_HI20, R_RISCV_LO12_I and R_RISCV_LO12_S
+    let (lui, lw, sw) = (word(0x14)?, word(0x18)?, word(0x1E)?);
+    assert_eq!(offset(imm_u(lui) as u32, imm_i(lw)), counter);
+    assert_eq!(offset(imm_u(lui) as u32, imm_s(sw)), counter);
+    // `c.beqz`, `c.j` and `c.jal`: R_RISCV_RVC_BRANCH and R_RISCV_RVC_JUMP
+    assert_eq!(offset(text + 0x22, imm_cb(word(0x22)? as u16)), text + 0x26);
+    assert_eq!(offset(text + 0x24, imm_cj(word(0x24)? as u16)), text + 0x28);
+    assert_eq!(offset(text + 0x26, imm_cj(word(0x26)? as u16)), helper);
+    // `beq` and `jal` without compression: R_RISCV_BRANCH and R_RISCV_JAL
+    assert_eq!(offset(text + 0x34, imm_b(word(0x34)?)), helper);
+    assert_eq!(offset(text + 0x38, imm_j(word(0x38)?)), helper);
+    // `auipc` with `sw`: R_RISCV_PCREL_HI20 and R_RISCV_PCREL_LO12_S
+    let (auipc, sw) = (word(0x3C)?, word(0x40)?);
+    assert_eq!(offset(offset(text + 0x3C, imm_u(auipc)), imm_s(sw)), counter);
+
+    // `table` in `.data` holds R_RISCV_32 words
+    assert_eq!(memory.read_u32(counter + 4)?, helper);
+    assert_eq!(memory.read_u32(counter + 8)?, message);
+    Ok(())
+}
+
+/// Place the allocated sections of `file` one after another from `0x8000_0000`, then load and relocate them.
+fn load(file: &[u8], buffer: &mut [u8]) -> Result<()> {
+    let elf = Elf::from_bytes(file)?;
+    let mut memory = Region::new(0x8000_0000, buffer);
+    let mut addresses = [None; 16];
+    let mut loader = ModuleLoader::new(&elf, &mut addresses)?;
+    let mut next = 0x8000_0000u32;
+    loader.place(|_, section| {
+        let align = section.header.alignment.get().max(1);
+        let address = next.div_ceil(align) * align;
+        next = address + section.header.size.get();
+        Ok(address)
+    })?;
+    loader.load(&mut memory)?;
+    loader.relocate(&mut memory, |_| Some(0x8000_1000))
+}
+
+/// Copy `file`, changing the type of relocation `entry` in the `SHT_RELA` section at `section`.
+fn retype(file: &[u8], section: u32, entry: u32, ty: RelocationType) -> Vec<u8> {
+    let elf = Elf::from_bytes(file).unwrap();
+    let offset = elf.section(section).unwrap().header.offset.get().as_usize().unwrap();
+    let mut file = file.to_vec();
+    // The type is the low byte of `r_info`
+    file[offset + entry as usize * 12 + 4] = u8::try_from(u32::from(ty)).unwrap();
+    file
+}
+
+#[test]
+fn missing_pcrel_hi20() {
+    // Drop the `R_RISCV_PCREL_HI20` of `la a0, message`, leaving its `R_RISCV_PCREL_LO12_I` with nothing to refer to
+    let file = retype(include_bytes!("../examples/module.o"), 3, 0, RelocationType::None);
+    let mut buffer = [0u8; 256];
+    assert!(matches!(load(&file, &mut buffer), Err(Error::MissingPcrelHi20 { address: 0x8000_0004 })));
+}
+
+#[test]
+fn thread_local() -> Result<()> {
+    let mut buffer = [0u8; 0x1000];
+    load(include_bytes!("../examples/tls.o"), &mut buffer)?;
+    let mut memory = Region::new(0x8000_0000, &mut buffer);
+    let mut word = |offset: u32| memory.read_u32(0x8000_0000 + offset);
+
+    // `second` is 0x900 bytes into `.tdata`, the only TLS section
+    let (lui, add, lw, sw) = (word(0x00)?, word(0x04)?, word(0x08)?, word(0x0C)?);
+    assert_eq!(offset(imm_u(lui) as u32, imm_i(lw)), 0x900);
+    assert_eq!(offset(imm_u(lui) as u32, imm_s(sw)), 0x900);
+    // R_RISCV_TPREL_ADD leaves `add a0, a0, tp` alone
+    assert_eq!(add, 0x0045_0533);
+
+    // R_RISCV_RVC_LUI: `c.lui a2, %hi(0x1E800)`, then `c.lui a3, %hi(0x7F0)` which becomes `c.li a3, 0`
+    let (lui, li) = (word(0x10)? as u16, word(0x12)? as u16);
+    assert_eq!(lui & 0xEF83, 0x6601);
+    assert_eq!(sign_extend(bits(lui.into(), 12, 12) << 5 | bits(lui.into(), 6, 2), 6) << 12, 0x1F000);
+    assert_eq!(li, 0x4681);
+    Ok(())
+}
+
+#[test]
+fn missing_got() {
+    for ty in [
+        RelocationType::GotHi20,
+        RelocationType::Got32Pcrel,
+        RelocationType::TlsGotHi20,
+        RelocationType::TlsGdHi20,
+        RelocationType::TlsDescHi20
+    ] {
+        let file = retype(include_bytes!("../examples/module.o"), 3, 0, ty);
+        let mut buffer = [0u8; 256];
+        let result = load(&file, &mut buffer);
+        assert!(matches!(result, Err(Error::MissingGot { ty: found, address: 0x8000_0004 }) if found == ty), "{ty:?}");
+    }
+}
@@ -0,0 +1,66 @@
+//! Runs the fuzz target's parsing paths over the example files and the malformed files in `fuzz/regressions`, each of
+//! which once caused a panic or hang. New crashes found by `cargo fuzz run parse` should be added there. Also checks
+//! that every example file is written back byte-identical by an unmodified `ElfEditor`.
+
+use std::{fs, path::Path};
+
+include!("../fuzz/parse.rs");
+
+fn parse_dir(dir: &Path, filter: impl Fn(&Path) -> bool) -> usize {
+    let mut count = 0;
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if !path.is_file() || !filter(&path) {
+            continue
+        }
+        let bytes = fs::read(&path).unwrap();
+        parse(&bytes);
+        // Headers are read in place, so also parse from an odd address
+        let mut shifted = vec![0; bytes.len() + 1];
+        shifted[1..].copy_from_slice(&bytes);
+        parse(&shifted[1..]);
+        // Every truncation of a file is also malformed
+        for len in (0..bytes.len()).step_by(bytes.len() / 64 + 1) {
+            parse(&bytes[..len]);
+        }
+        count += 1;
+    }
+    count
+}
+
+#[test]
+fn examples() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let count = parse_dir(&dir, |path| matches!(path.extension().and_then(|e| e.to_str()), Some("elf" | "o" | "so")));
+    assert!(count > 0);
+}
+
+#[test]
+#[cfg_attr(miri, ignore = "too slow under Miri")]
+fn regressions() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/regressions");
+    let count = parse_dir(&dir, |_| true);
+    assert!(count > 0);
+}
+
+#[test]
+fn round_trip() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let mut count = 0;
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if !matches!(path.extension().and_then(|e| e.to_str()), Some("elf" | "o" | "so")) {
+            continue
+        }
+        let bytes = fs::read(&path).unwrap();
+        let Ok(elf) = Elf::from_bytes(&bytes) else { continue };
+        let mut sections = [None; 64];
+        let mut symbols = [None; 0];
+        let editor = ElfEditor::new(&elf, &mut sections, &mut symbols).unwrap();
+        let mut output = vec![0; editor.size().unwrap()];
+        editor.write(&mut SliceSink::new(&mut output)).unwrap();
+        assert!(output == bytes, "{} changed", path.display());
+        count += 1;
+    }
+    assert!(count > 0);
+}